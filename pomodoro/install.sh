@@ -17,7 +17,7 @@ if [ -f "tauri-app/package.json" ]; then
     npm install
     npm run tauri build
     cd ..
-    TARGET_APP="tauri-app/src-tauri/target/release/bundle/macos/$APP_NAME.app"
+    TARGET_APP="tauri-app/target/release/bundle/macos/$APP_NAME.app"
 else
     # Remote Install Mode: Download DMG
     echo "⬇️   Downloading latest release..."
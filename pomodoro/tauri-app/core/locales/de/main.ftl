@@ -0,0 +1,143 @@
+# Tray menu
+
+menu-pause = Pause
+menu-resume = Fortsetzen
+menu-extend = Verlängern +5 Min.
+menu-start-focus = Fokus starten
+menu-forfeit = Abbrechen
+menu-start-break = Pause starten
+menu-start-break-now = Jetzt Pause machen
+menu-skip-break = Pause überspringen
+menu-calm-mode = Ruhemodus
+menu-minutes = { $minutes } Minuten
+menu-minutes-default = { $minutes } Minuten (Standard)
+menu-sessions-today =
+    { $count ->
+        [one] { $count } volle Sitzung heute
+       *[other] { $count } volle Sitzungen heute
+    }
+menu-history = Verlauf: { $chart }
+menu-export = Exportieren…
+menu-export-csv = CSV
+menu-export-jsonl = JSON Lines
+menu-export-ics = Kalender (.ics)
+menu-settings = Einstellungen…
+menu-launch-at-login = Bei Anmeldung starten
+menu-how-to-use = Anleitung
+menu-quit = Beenden
+menu-settings-error = ⚠️ settings.json: { $error }
+menu-history-error = ⚠️ Verlauf nicht verfügbar: { $error }
+
+# Tray tooltip
+
+tooltip-idle = Bereit
+tooltip-focus = Fokus
+tooltip-break = Pause
+tooltip-calm = Ruhe
+tooltip-paused = Angehalten
+tooltip-remaining = noch { $minutes } Min. · endet um { $end }
+tooltip-remaining-paused = noch { $minutes } Min.
+tooltip-long-break =
+    { $count ->
+        [one] noch { $count } Sitzung bis zur langen Pause
+       *[other] noch { $count } Sitzungen bis zur langen Pause
+    }
+tooltip-focus-today = heute { $minutes } Min. fokussiert
+
+# Break Sanctuary (index.html, main.js)
+
+sanctuary-label-1 = Atme ein
+sanctuary-label-2 = Sei präsent
+sanctuary-label-3 = Ruhe deine Augen aus
+sanctuary-label-4 = Lass los
+sanctuary-label-5 = Sei still
+sanctuary-subtitle-1 = Dein Geist kommt zur Ruhe
+sanctuary-subtitle-2 = Dieser Moment gehört dir
+sanctuary-subtitle-3 = Stille gibt Kraft
+sanctuary-subtitle-4 = Frieden fließt durch dich
+sanctuary-subtitle-5 = Atme tief
+sanctuary-end-early = Pause vorzeitig beenden
+sanctuary-stillness-hint = Klicke irgendwo, um zur Stille zurückzukehren
+sanctuary-complete = Bereit für den Fokus
+
+# Micro-break (microbreak.html, microbreak.js)
+
+microbreak-title-1 = Schau weg vom Bildschirm
+microbreak-tip-1 = Fokussiere etwas in sechs Metern Entfernung
+microbreak-title-2 = Ruhe deine Augen aus
+microbreak-tip-2 = Blinzle zehnmal langsam
+microbreak-title-3 = Entspanne deinen Blick
+microbreak-tip-3 = Schließe kurz die Augen
+microbreak-title-4 = Dehne deinen Nacken
+microbreak-tip-4 = Rolle die Schultern sanft nach hinten
+microbreak-title-5 = Atme durch
+microbreak-tip-5 = Tief einatmen, langsam ausatmen
+microbreak-continue = Weiterarbeiten
+microbreak-auto-close = Schließt in { $seconds } Sekunden
+
+# Settings (settings.html, settings.js)
+
+settings-title = Einstellungen
+settings-durations = Dauer
+settings-focus = Fokus
+settings-short-break = Kurze Pause
+settings-long-break = Lange Pause
+settings-long-break-every = Lange Pause alle
+settings-calm = Ruhemodus
+settings-micro-breaks = Mikropausen
+settings-enabled = Aktiviert
+settings-every = Alle
+settings-general = Allgemein
+settings-language = Sprache
+settings-language-system = System
+settings-launch-at-login = Bei Anmeldung starten
+settings-menu-bar = Menüleiste
+settings-title-template = Titel
+settings-digits = Ziffern
+settings-digits-monospace = Feste Breite
+settings-digits-ascii = Einfach
+settings-icon = Symbol
+settings-icon-ring = Fortschrittsring
+settings-icon-pie = Fortschrittskreis
+settings-icon-glyph = App-Symbol
+settings-daily-goal = Tagesziel
+settings-shortcuts = Tastenkürzel
+settings-shortcut-toggle = Starten / Pause
+settings-shortcut-forfeit = Abbrechen
+settings-shortcut-extend = Verlängern +5 Min.
+settings-shortcut-stats = Statistik öffnen
+settings-shortcut-capture = Schnellnotiz
+settings-unit-minutes = Min.
+settings-unit-sessions = Sitzungen
+settings-close = Schließen
+settings-save = Speichern
+settings-saved = Gespeichert
+
+# Stats (stats.html, stats.js)
+
+stats-title = Fokus-Statistik
+stats-heading = Deine Fokus-Reise
+stats-today = Fokus heute
+stats-unit-minutes = Min.
+stats-sessions = Sitzungen
+stats-last-7-days = Letzte 7 Tage
+stats-bar-minutes = { $minutes } Min.
+stats-weekday-0 = So
+stats-weekday-1 = Mo
+stats-weekday-2 = Di
+stats-weekday-3 = Mi
+stats-weekday-4 = Do
+stats-weekday-5 = Fr
+stats-weekday-6 = Sa
+stats-insight = „Der Geist ist wie Wasser. Ist er aufgewühlt, sieht man schwer hindurch. Ist er ruhig, wird alles klar.“
+stats-export = Exportieren
+stats-export-ics = Kalender
+stats-saved-to = Gespeichert unter { $path }
+stats-export-failed = Export fehlgeschlagen: { $error }
+stats-close = Schließen
+
+# Quick capture (capture.html)
+
+capture-title = Schnellnotiz
+capture-placeholder = Wofür ist diese Sitzung? #tags
+capture-hint = Enter zum Speichern · Esc zum Schließen
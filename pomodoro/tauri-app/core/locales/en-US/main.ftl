@@ -0,0 +1,144 @@
+# Tray menu
+
+menu-pause = Pause
+menu-resume = Resume
+menu-extend = Extend +5 min
+menu-start-focus = Start Focus
+menu-forfeit = Forfeit
+menu-start-break = Start Break
+menu-start-break-now = Start Break Now
+menu-skip-break = Skip Break
+menu-calm-mode = Calm Mode
+menu-minutes = { $minutes } Minutes
+menu-minutes-default = { $minutes } Minutes (Default)
+menu-sessions-today =
+    { $count ->
+        [one] { $count } Full Session Today
+       *[other] { $count } Full Sessions Today
+    }
+menu-history = History: { $chart }
+menu-export = Export…
+menu-export-csv = CSV
+menu-export-jsonl = JSON Lines
+menu-export-ics = Calendar (.ics)
+menu-settings = Settings…
+menu-launch-at-login = Launch at Login
+menu-how-to-use = How to Use
+menu-quit = Quit
+menu-settings-error = ⚠️ settings.json: { $error }
+menu-history-error = ⚠️ History unavailable: { $error }
+
+# Tray tooltip
+
+tooltip-idle = Ready
+tooltip-focus = Focus
+tooltip-break = Break
+tooltip-calm = Calm
+tooltip-paused = Paused
+tooltip-remaining = { $minutes } min left · ends { $end }
+tooltip-remaining-paused = { $minutes } min left
+tooltip-long-break =
+    { $count ->
+        [one] { $count } session until a long break
+       *[other] { $count } sessions until a long break
+    }
+tooltip-focus-today = { $minutes } min focused today
+
+# Break Sanctuary (index.html, main.js)
+# Window messages are filled in by JavaScript: keep them to plain { $variables }, no selectors.
+
+sanctuary-label-1 = Take a breath
+sanctuary-label-2 = Be present
+sanctuary-label-3 = Rest your eyes
+sanctuary-label-4 = Let go
+sanctuary-label-5 = Be still
+sanctuary-subtitle-1 = Your mind is settling
+sanctuary-subtitle-2 = This moment is yours
+sanctuary-subtitle-3 = Stillness restores
+sanctuary-subtitle-4 = Peace flows through you
+sanctuary-subtitle-5 = Breathe deeply
+sanctuary-end-early = End break early
+sanctuary-stillness-hint = Click anywhere to return to stillness
+sanctuary-complete = Ready to focus
+
+# Micro-break (microbreak.html, microbreak.js)
+
+microbreak-title-1 = Look away from screen
+microbreak-tip-1 = Focus on something 20 feet away
+microbreak-title-2 = Rest your eyes
+microbreak-tip-2 = Blink slowly 10 times
+microbreak-title-3 = Relax your gaze
+microbreak-tip-3 = Close your eyes for a moment
+microbreak-title-4 = Stretch your neck
+microbreak-tip-4 = Roll your shoulders back gently
+microbreak-title-5 = Take a breath
+microbreak-tip-5 = Deep breath in, slow breath out
+microbreak-continue = Continue Working
+microbreak-auto-close = Auto-closes in { $seconds } seconds
+
+# Settings (settings.html, settings.js)
+
+settings-title = Settings
+settings-durations = Durations
+settings-focus = Focus
+settings-short-break = Short break
+settings-long-break = Long break
+settings-long-break-every = Long break every
+settings-calm = Calm mode
+settings-micro-breaks = Micro Breaks
+settings-enabled = Enabled
+settings-every = Every
+settings-general = General
+settings-language = Language
+settings-language-system = System
+settings-launch-at-login = Launch at login
+settings-menu-bar = Menu Bar
+settings-title-template = Title
+settings-digits = Digits
+settings-digits-monospace = Monospace
+settings-digits-ascii = Plain
+settings-icon = Icon
+settings-icon-ring = Progress ring
+settings-icon-pie = Progress pie
+settings-icon-glyph = App icon
+settings-daily-goal = Daily goal
+settings-shortcuts = Shortcuts
+settings-shortcut-toggle = Start / pause
+settings-shortcut-forfeit = Forfeit
+settings-shortcut-extend = Extend +5 min
+settings-shortcut-stats = Open stats
+settings-shortcut-capture = Quick capture
+settings-unit-minutes = min
+settings-unit-sessions = sessions
+settings-close = Close
+settings-save = Save
+settings-saved = Saved
+
+# Stats (stats.html, stats.js). Weekdays run Sunday (0) to Saturday (6).
+
+stats-title = Focus Stats
+stats-heading = Your Focus Journey
+stats-today = Today's Focus
+stats-unit-minutes = min
+stats-sessions = Sessions
+stats-last-7-days = Last 7 Days
+stats-bar-minutes = { $minutes }m
+stats-weekday-0 = Sun
+stats-weekday-1 = Mon
+stats-weekday-2 = Tue
+stats-weekday-3 = Wed
+stats-weekday-4 = Thu
+stats-weekday-5 = Fri
+stats-weekday-6 = Sat
+stats-insight = “The mind is like water. When it is turbulent, it is difficult to see. When it is calm, everything becomes clear.”
+stats-export = Export
+stats-export-ics = Calendar
+stats-saved-to = Saved to { $path }
+stats-export-failed = Export failed: { $error }
+stats-close = Close
+
+# Quick capture (capture.html)
+
+capture-title = Quick Capture
+capture-placeholder = What is this session for? #tags
+capture-hint = Enter to save · Esc to close
@@ -0,0 +1,249 @@
+use serde::{Serialize, Deserialize};
+use crate::persistence::{self, Phase, SessionRecord};
+use crate::timer::{Status, TimerState, TimerType};
+
+/// Shortest and longest session a command may start, in seconds (settings allow 1–180 minutes).
+pub const MIN_DURATION: u64 = 60;
+pub const MAX_DURATION: u64 = 180 * 60;
+/// Longest label or forfeit reason, in characters.
+pub const MAX_TEXT: usize = 200;
+
+/// Everything that changes the timer. The tray menu, global shortcuts and the webview
+/// commands all turn their input into one of these and go through `apply`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Action {
+    /// `duration` in seconds; `None` uses the configured focus length.
+    StartFocus { duration: Option<u64>, label: Option<String> },
+    StartBreak,
+    SkipBreak,
+    Pause,
+    Resume,
+    Toggle,
+    Forfeit { reason: Option<String> },
+    Extend,
+    /// `duration` in seconds; `None` uses the configured calm length.
+    StartCalm { duration: Option<u64> },
+    Reset,
+}
+
+impl Action {
+    /// The action behind a tray menu item or shortcut id, if it is a timer action.
+    pub fn from_menu_id(id: &str) -> Option<Action> {
+        let minutes = |prefix: &str| id.strip_prefix(prefix)?.parse::<u64>().ok().map(|m| m * 60);
+        Some(match id {
+            "focus_default" => Action::StartFocus { duration: None, label: None },
+            "calm_default" => Action::StartCalm { duration: None },
+            "start_break" => Action::StartBreak,
+            "skip_break" => Action::SkipBreak,
+            "toggle" => Action::Toggle,
+            "forfeit" => Action::Forfeit { reason: None },
+            "extend" => Action::Extend,
+            "reset" => Action::Reset,
+            _ if id.starts_with("focus_") => Action::StartFocus { duration: Some(minutes("focus_")?), label: None },
+            _ if id.starts_with("calm_") => Action::StartCalm { duration: Some(minutes("calm_")?) },
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    InvalidDuration,
+    InvalidText,
+    /// A session is already running or paused.
+    Busy,
+    NotRunning,
+    NotPaused,
+    NoBreak,
+    BreakRunning,
+    ExtendLimit,
+}
+
+/// Returned to the webviews as `{ "code": "...", "message": "..." }`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ActionError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl ActionError {
+    fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
+/// What the caller has to do besides saving the new state.
+#[derive(Debug, Default, PartialEq)]
+pub struct Effects {
+    /// Sessions to record in history.
+    pub log: Vec<SessionRecord>,
+    /// `Some(true)` to show the Sanctuary, `Some(false)` to hide it.
+    pub sanctuary: Option<bool>,
+}
+
+fn check_duration(duration: Option<u64>, default: u64) -> Result<u64, ActionError> {
+    let duration = duration.unwrap_or(default);
+    if !(MIN_DURATION..=MAX_DURATION).contains(&duration) {
+        return Err(ActionError::new(
+            ErrorCode::InvalidDuration,
+            format!("duration must be between {} and {} seconds (got {})", MIN_DURATION, MAX_DURATION, duration),
+        ));
+    }
+    Ok(duration)
+}
+
+/// Trimmed; empty text is `None`.
+fn check_text(name: &str, text: Option<String>) -> Result<Option<String>, ActionError> {
+    let text = text.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+    if text.as_ref().is_some_and(|t| t.chars().count() > MAX_TEXT) {
+        return Err(ActionError::new(ErrorCode::InvalidText, format!("{} must be at most {} characters", name, MAX_TEXT)));
+    }
+    Ok(text)
+}
+
+fn focusing(state: &TimerState) -> bool {
+    state.timer_type == TimerType::Focus && matches!(state.status, Status::Focus | Status::Paused)
+}
+
+/// A focus session cut short, with whatever the user captured for it.
+fn forfeited(state: &mut TimerState, reason: Option<String>) -> SessionRecord {
+    let minutes = (state.total.saturating_sub(state.remaining) / 60) as u32;
+    let (label, tags) = state.take_capture();
+    SessionRecord { reason, ..persistence::session_record(Phase::Focus, false, minutes, label, tags) }
+}
+
+/// Validate `action` against the current state and apply it. On error `state` is unchanged.
+pub fn apply(state: &mut TimerState, action: Action) -> Result<Effects, ActionError> {
+    let mut effects = Effects::default();
+    match action {
+        Action::StartFocus { duration, label } => {
+            let duration = check_duration(duration, state.config.focus)?;
+            let label = check_text("label", label)?;
+            if state.status != Status::Idle {
+                return Err(ActionError::new(ErrorCode::Busy, "a session is already running"));
+            }
+            state.start_focus(duration);
+            if label.is_some() {
+                state.label = label;
+            }
+        }
+        Action::StartBreak => {
+            if state.has_break() && state.status != Status::Idle {
+                return Err(ActionError::new(ErrorCode::BreakRunning, "a break is already running"));
+            }
+            // The focus session it cuts short counts as forfeited
+            if focusing(state) {
+                effects.log.push(forfeited(state, None));
+            }
+            state.start_break_now();
+            effects.sanctuary = Some(true);
+        }
+        Action::SkipBreak => {
+            let minutes = state
+                .skip_break()
+                .ok_or_else(|| ActionError::new(ErrorCode::NoBreak, "there is no break to skip"))?;
+            effects.log.push(persistence::session_record(Phase::SkippedBreak, false, minutes, None, Vec::new()));
+            effects.sanctuary = Some(false);
+        }
+        Action::Pause => {
+            if !matches!(state.status, Status::Focus | Status::Break) {
+                return Err(ActionError::new(ErrorCode::NotRunning, "nothing is running"));
+            }
+            state.toggle();
+        }
+        Action::Resume => {
+            if state.status != Status::Paused {
+                return Err(ActionError::new(ErrorCode::NotPaused, "nothing is paused"));
+            }
+            state.toggle();
+        }
+        Action::Toggle => state.toggle(),
+        // Forfeiting a break means skipping it
+        Action::Forfeit { .. } if state.has_break() => return apply(state, Action::SkipBreak),
+        Action::Forfeit { reason } => {
+            let reason = check_text("reason", reason)?;
+            if !focusing(state) {
+                return Err(ActionError::new(ErrorCode::NotRunning, "there is no focus session to forfeit"));
+            }
+            effects.log.push(forfeited(state, reason));
+            state.forfeit();
+        }
+        Action::Extend => {
+            if !state.extend() {
+                return Err(ActionError::new(ErrorCode::ExtendLimit, "only a focus session can be extended, up to 5 times"));
+            }
+        }
+        Action::StartCalm { duration } => {
+            let duration = check_duration(duration, state.config.calm)?;
+            if focusing(state) {
+                effects.log.push(forfeited(state, None));
+            }
+            state.start_break(duration);
+            state.timer_type = TimerType::Calm;
+            effects.sanctuary = Some(true);
+        }
+        Action::Reset => state.reset(),
+    }
+    Ok(effects)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn idle() -> TimerState {
+        let mut state = TimerState::default();
+        state.reset();
+        state
+    }
+
+    #[test]
+    fn test_menu_ids() {
+        assert_eq!(Action::from_menu_id("focus_45"), Some(Action::StartFocus { duration: Some(2700), label: None }));
+        assert_eq!(Action::from_menu_id("calm_default"), Some(Action::StartCalm { duration: None }));
+        assert_eq!(Action::from_menu_id("forfeit"), Some(Action::Forfeit { reason: None }));
+        assert_eq!(Action::from_menu_id("focus_x"), None);
+        assert_eq!(Action::from_menu_id("settings"), None);
+    }
+
+    #[test]
+    fn test_validation_leaves_state_alone() {
+        let mut state = idle();
+        let err = apply(&mut state, Action::StartFocus { duration: Some(5), label: None }).unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidDuration);
+        let err = apply(&mut state, Action::StartFocus { duration: None, label: Some("x".repeat(201)) }).unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidText);
+        assert_eq!(apply(&mut state, Action::Pause).unwrap_err().code, ErrorCode::NotRunning);
+        assert_eq!(apply(&mut state, Action::Forfeit { reason: None }).unwrap_err().code, ErrorCode::NotRunning);
+        assert_eq!(state, idle());
+
+        apply(&mut state, Action::StartFocus { duration: Some(600), label: Some("  Essay ".to_string()) }).unwrap();
+        assert_eq!(state.label.as_deref(), Some("Essay"));
+        assert_eq!(apply(&mut state, Action::StartFocus { duration: None, label: None }).unwrap_err().code, ErrorCode::Busy);
+        assert_eq!(apply(&mut state, Action::Resume).unwrap_err().code, ErrorCode::NotPaused);
+    }
+
+    #[test]
+    fn test_forfeit_and_breaks_are_logged() {
+        let mut state = idle();
+        apply(&mut state, Action::StartFocus { duration: None, label: Some("Essay".to_string()) }).unwrap();
+        state.remaining -= 600;
+        let effects = apply(&mut state, Action::Forfeit { reason: Some("meeting".to_string()) }).unwrap();
+        let record = &effects.log[0];
+        assert_eq!((record.phase, record.completed, record.minutes), (Phase::Focus, false, 10));
+        assert_eq!((record.label.as_deref(), record.reason.as_deref()), (Some("Essay"), Some("meeting")));
+
+        // Taking a break early forfeits the focus session and opens the Sanctuary
+        apply(&mut state, Action::StartFocus { duration: None, label: None }).unwrap();
+        let effects = apply(&mut state, Action::StartBreak).unwrap();
+        assert_eq!((effects.log.len(), effects.sanctuary), (1, Some(true)));
+        assert_eq!(apply(&mut state, Action::StartBreak).unwrap_err().code, ErrorCode::BreakRunning);
+
+        // Forfeiting a break skips it
+        let effects = apply(&mut state, Action::Forfeit { reason: None }).unwrap();
+        assert_eq!((effects.log[0].phase, effects.sanctuary), (Phase::SkippedBreak, Some(false)));
+        assert_eq!(apply(&mut state, Action::SkipBreak).unwrap_err().code, ErrorCode::NoBreak);
+    }
+}
@@ -0,0 +1,300 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::settings::{AutostartMethod, Settings};
+
+const APP_NAME: &str = "Break Sanctuary";
+const APP_ID: &str = "com.pomodoro.native";
+const UNIT_NAME: &str = "pomodoro-native.service";
+const SESSION_TARGET: &str = "graphical-session.target";
+
+fn xdg_entry_path(home: &Path) -> PathBuf {
+    home.join(".config/autostart").join(format!("{}.desktop", APP_ID))
+}
+
+fn systemd_unit_path(home: &Path) -> PathBuf {
+    home.join(".config/systemd/user").join(UNIT_NAME)
+}
+
+/// What `systemctl --user enable` would create.
+fn systemd_wants_path(home: &Path) -> PathBuf {
+    home.join(".config/systemd/user")
+        .join(format!("{}.wants", SESSION_TARGET))
+        .join(UNIT_NAME)
+}
+
+#[cfg(target_os = "macos")]
+fn launch_agent_path(home: &Path) -> PathBuf {
+    home.join("Library/LaunchAgents").join(format!("{}.plist", APP_ID))
+}
+
+/// Quote a path for a `.desktop` Exec key. `%` starts a field code, so it is doubled.
+fn desktop_quote(exe: &Path) -> String {
+    let mut quoted = String::from("\"");
+    for c in exe.to_string_lossy().chars() {
+        match c {
+            '"' | '`' | '$' | '\\' => quoted.push('\\'),
+            '%' => quoted.push('%'),
+            _ => {}
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Quote a path for a systemd ExecStart line.
+fn systemd_quote(exe: &Path) -> String {
+    let escaped = exe
+        .to_string_lossy()
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('%', "%%");
+    format!("\"{}\"", escaped)
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+pub fn desktop_entry(exe: &Path) -> String {
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name={}\n\
+         Exec={}\n\
+         Terminal=false\n\
+         X-GNOME-Autostart-enabled=true\n",
+        APP_NAME,
+        desktop_quote(exe)
+    )
+}
+
+pub fn systemd_unit(exe: &Path) -> String {
+    format!(
+        "[Unit]\n\
+         Description={}\n\
+         PartOf={target}\n\
+         After={target}\n\
+         \n\
+         [Service]\n\
+         ExecStart={}\n\
+         Restart=on-failure\n\
+         \n\
+         [Install]\n\
+         WantedBy={target}\n",
+        APP_NAME,
+        systemd_quote(exe),
+        target = SESSION_TARGET
+    )
+}
+
+pub fn launch_agent(exe: &Path) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+         <plist version=\"1.0\">\n\
+         <dict>\n\
+         \t<key>Label</key>\n\
+         \t<string>{}</string>\n\
+         \t<key>ProgramArguments</key>\n\
+         \t<array>\n\
+         \t\t<string>{}</string>\n\
+         \t</array>\n\
+         \t<key>RunAtLoad</key>\n\
+         \t<true/>\n\
+         </dict>\n\
+         </plist>\n",
+        APP_ID,
+        xml_escape(&exe.to_string_lossy())
+    )
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Could not create {}: {}", parent.display(), e))?;
+    }
+    fs::write(path, contents).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
+
+fn remove_file(path: &Path) -> Result<(), String> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Could not remove {}: {}", path.display(), e)),
+    }
+}
+
+/// Install the Linux entry for `method`, removing the other one so only one launches the app.
+pub fn enable_linux(home: &Path, exe: &Path, method: AutostartMethod) -> Result<(), String> {
+    disable_linux(home)?;
+    match method {
+        AutostartMethod::Xdg => write_file(&xdg_entry_path(home), &desktop_entry(exe)),
+        AutostartMethod::Systemd => {
+            let unit = systemd_unit_path(home);
+            write_file(&unit, &systemd_unit(exe))?;
+            let wants = systemd_wants_path(home);
+            fs::create_dir_all(wants.parent().unwrap())
+                .map_err(|e| format!("Could not create {}: {}", wants.display(), e))?;
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(&unit, &wants)
+                .map_err(|e| format!("Could not enable {}: {}", UNIT_NAME, e))?;
+            Ok(())
+        }
+    }
+}
+
+pub fn disable_linux(home: &Path) -> Result<(), String> {
+    remove_file(&xdg_entry_path(home))?;
+    remove_file(&systemd_wants_path(home))?;
+    remove_file(&systemd_unit_path(home))
+}
+
+pub fn linux_enabled(home: &Path) -> bool {
+    xdg_entry_path(home).exists() || systemd_wants_path(home).exists()
+}
+
+#[cfg(windows)]
+const RUN_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+
+#[cfg(windows)]
+fn reg(args: &[&str]) -> Result<bool, String> {
+    std::process::Command::new("reg")
+        .args(args)
+        .output()
+        .map(|out| out.status.success())
+        .map_err(|e| format!("Could not run reg.exe: {}", e))
+}
+
+/// Install or refresh the login entry. Rewriting an existing entry picks up a moved executable.
+#[cfg(target_os = "macos")]
+pub fn enable(home: &Path, exe: &Path, _method: AutostartMethod) -> Result<(), String> {
+    write_file(&launch_agent_path(home), &launch_agent(exe))
+}
+
+#[cfg(target_os = "macos")]
+pub fn disable(home: &Path) -> Result<(), String> {
+    remove_file(&launch_agent_path(home))
+}
+
+#[cfg(target_os = "macos")]
+pub fn is_enabled(home: &Path) -> bool {
+    launch_agent_path(home).exists()
+}
+
+#[cfg(windows)]
+pub fn enable(_home: &Path, exe: &Path, _method: AutostartMethod) -> Result<(), String> {
+    let command = format!("\"{}\"", exe.display());
+    match reg(&["add", RUN_KEY, "/v", APP_NAME, "/t", "REG_SZ", "/d", &command, "/f"])? {
+        true => Ok(()),
+        false => Err("Could not add the login registry entry".to_string()),
+    }
+}
+
+#[cfg(windows)]
+pub fn disable(home: &Path) -> Result<(), String> {
+    if is_enabled(home) {
+        reg(&["delete", RUN_KEY, "/v", APP_NAME, "/f"])?;
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+pub fn is_enabled(_home: &Path) -> bool {
+    reg(&["query", RUN_KEY, "/v", APP_NAME]).unwrap_or(false)
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+pub fn enable(home: &Path, exe: &Path, method: AutostartMethod) -> Result<(), String> {
+    enable_linux(home, exe, method)
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+pub fn disable(home: &Path) -> Result<(), String> {
+    disable_linux(home)
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+pub fn is_enabled(home: &Path) -> bool {
+    linux_enabled(home)
+}
+
+fn home_dir() -> Result<PathBuf, String> {
+    home::home_dir().ok_or_else(|| "Could not find home directory".to_string())
+}
+
+/// The binary to launch. AppImages run from a temporary mount, so point at the image itself.
+fn current_exe() -> Result<PathBuf, String> {
+    if let Some(appimage) = std::env::var_os("APPIMAGE") {
+        return Ok(PathBuf::from(appimage));
+    }
+    std::env::current_exe().map_err(|e| format!("Could not locate the app executable: {}", e))
+}
+
+pub fn enabled() -> bool {
+    home_dir().map(|home| is_enabled(&home)).unwrap_or(false)
+}
+
+/// Make the login entry match `settings.launch_at_login`.
+pub fn sync(settings: &Settings) -> Result<(), String> {
+    let home = home_dir()?;
+    if settings.launch_at_login {
+        enable(&home, &current_exe()?, settings.autostart)
+    } else if is_enabled(&home) {
+        disable(&home)
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn test_xdg_desktop_entry() {
+        let home = TempDir::new("autostart-xdg");
+        let exe = Path::new("/opt/Break Sanctuary/pomodoro-native");
+        enable_linux(&home, exe, AutostartMethod::Xdg).unwrap();
+
+        let entry = fs::read_to_string(home.join(".config/autostart/com.pomodoro.native.desktop")).unwrap();
+        assert!(entry.starts_with("[Desktop Entry]\n"));
+        assert!(entry.contains("Type=Application\n"));
+        assert!(entry.contains("Exec=\"/opt/Break Sanctuary/pomodoro-native\"\n"));
+        assert!(linux_enabled(&home));
+
+        disable_linux(&home).unwrap();
+        assert!(!linux_enabled(&home));
+        disable_linux(&home).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_systemd_unit_replaces_xdg_entry() {
+        let home = TempDir::new("autostart-systemd");
+        let exe = Path::new("/usr/bin/pomodoro-native");
+        enable_linux(&home, exe, AutostartMethod::Xdg).unwrap();
+        enable_linux(&home, exe, AutostartMethod::Systemd).unwrap();
+
+        assert!(!home.join(".config/autostart/com.pomodoro.native.desktop").exists());
+        let unit_path = home.join(".config/systemd/user/pomodoro-native.service");
+        let unit = fs::read_to_string(&unit_path).unwrap();
+        assert!(unit.contains("ExecStart=\"/usr/bin/pomodoro-native\"\n"));
+        assert!(unit.contains("WantedBy=graphical-session.target\n"));
+
+        let wants = home.join(".config/systemd/user/graphical-session.target.wants/pomodoro-native.service");
+        assert_eq!(fs::read_link(&wants).unwrap(), unit_path);
+        assert!(linux_enabled(&home));
+
+        disable_linux(&home).unwrap();
+        assert!(!unit_path.exists() && !wants.exists());
+    }
+
+    #[test]
+    fn test_exec_paths_are_escaped() {
+        let exe = Path::new("/home/a$b/100%/pomodoro");
+        assert!(desktop_entry(exe).contains("Exec=\"/home/a\\$b/100%%/pomodoro\"\n"));
+        assert!(systemd_unit(exe).contains("ExecStart=\"/home/a$b/100%%/pomodoro\"\n"));
+        assert!(launch_agent(Path::new("/Apps/R&D.app")).contains("<string>/Apps/R&amp;D.app</string>"));
+    }
+}
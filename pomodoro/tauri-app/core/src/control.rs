@@ -0,0 +1,397 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use interprocess::local_socket::{prelude::*, ListenerOptions, Listener, Name, Stream};
+use serde::{Deserialize, Serialize};
+use crate::action::{Action, ActionError};
+use crate::events::LifecycleEvent;
+use crate::import::{ImportFormat, ImportSummary};
+use crate::persistence::HistoryEntry;
+use crate::settings::EncryptionKey;
+use crate::timer::TimerState;
+
+/// The socket file in the data directory (Unix).
+pub const SOCKET_FILE: &str = "control.sock";
+
+/// A Unix socket in the data directory; on Windows, a named pipe named after it.
+pub fn socket_name(dir: &Path) -> io::Result<Name<'static>> {
+    #[cfg(unix)]
+    {
+        use interprocess::local_socket::GenericFilePath;
+        dir.join(SOCKET_FILE).to_fs_name::<GenericFilePath>().map(|name| name.into_owned())
+    }
+    #[cfg(windows)]
+    {
+        use interprocess::local_socket::GenericNamespaced;
+        // Pipe names are machine-wide; the data directory makes them per user
+        format!("pomodoro-{}", dir.display().to_string().replace('\\', "/"))
+            .to_ns_name::<GenericNamespaced>()
+            .map(|name| name.into_owned())
+    }
+}
+
+/// One request per line, answered by one `Reply` line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Request {
+    /// `{"action": "start_focus", "duration": 1500}`, as in `Action`.
+    Action(Action),
+    /// `{"query": "state"}` or `{"query": "history", "from": "2026-03-01", "to": "2026-03-07"}`.
+    Query(Query),
+    /// `{"subscribe": true}`: after the reply, lifecycle events follow one per line until the
+    /// connection closes.
+    Subscribe { subscribe: bool },
+    /// `{"flush": true}`: reply once every session logged so far is on disk, e.g. before
+    /// reading history files directly.
+    Flush { flush: bool },
+    /// `{"import": "/path/to/toggl.csv", "format": "toggl"}`, with the `ImportFormat` fields
+    /// alongside: merge the file into history and show it in the app.
+    Import {
+        import: PathBuf,
+        #[serde(flatten)]
+        format: ImportFormat,
+    },
+    /// `{"encrypt": {"source": "keyfile", "path": "..."}}` or `{"decrypt": true}`: convert
+    /// history in place and record the choice in `settings.json`.
+    Encrypt { encrypt: EncryptionKey },
+    Decrypt { decrypt: bool },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "query", rename_all = "snake_case")]
+pub enum Query {
+    State,
+    /// Daily totals between two `YYYY-MM-DD` dates, inclusive.
+    History { from: String, to: String },
+}
+
+/// `{"state": {...}}`, `{"history": [...]}`, `{"subscribed": true}`, `{"flushed": true}`,
+/// `{"imported": {...}}`, `{"encrypted": true}` or `{"error": {"code": "...", "message": "..."}}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reply {
+    State(TimerState),
+    History(Vec<HistoryEntry>),
+    Subscribed(bool),
+    Flushed(bool),
+    Imported(ImportSummary),
+    /// Whether history is now encrypted.
+    Encrypted(bool),
+    Error(ReplyError),
+}
+
+/// `code` is an action's `ErrorCode`, or `invalid_request` for a line that does not parse.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplyError {
+    pub code: String,
+    pub message: String,
+}
+
+impl From<ActionError> for ReplyError {
+    fn from(error: ActionError) -> Self {
+        let code = serde_json::to_value(error.code).ok().and_then(|v| v.as_str().map(String::from));
+        Self { code: code.unwrap_or_default(), message: error.message }
+    }
+}
+
+/// A lifecycle event sent to subscribers: `{"event": "session-completed", "data": {...}}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    pub event: String,
+    pub data: serde_json::Value,
+}
+
+/// What the socket can reach in the running app.
+pub trait Backend: Send + Sync {
+    fn dispatch(&self, action: Action) -> Result<TimerState, ActionError>;
+    fn state(&self) -> TimerState;
+    fn history(&self, from: &str, to: &str) -> Vec<HistoryEntry>;
+    /// Write out the sessions still queued for the store.
+    fn flush(&self);
+    fn import(&self, path: &Path, format: &ImportFormat) -> Result<ImportSummary, String>;
+    /// Encrypt history with `key`, or decrypt it for `None`.
+    fn set_encryption(&self, key: Option<EncryptionKey>) -> Result<(), String>;
+}
+
+/// Connections that asked for lifecycle events. Closed connections are dropped on the next publish.
+pub struct Subscribers(Mutex<Vec<Sender<String>>>);
+
+impl Subscribers {
+    pub const fn new() -> Self {
+        Self(Mutex::new(Vec::new()))
+    }
+
+    pub fn publish(&self, event: &LifecycleEvent) {
+        let mut senders = self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if senders.is_empty() {
+            return;
+        }
+        let event = Event { event: event.name().to_string(), data: serde_json::to_value(event).unwrap_or_default() };
+        let line = serde_json::to_string(&event).expect("Failed to serialize event");
+        senders.retain(|sender| sender.send(line.clone()).is_ok());
+    }
+
+    fn subscribe(&self) -> Receiver<String> {
+        let (sender, receiver) = mpsc::channel();
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push(sender);
+        receiver
+    }
+}
+
+impl Default for Subscribers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn invalid(message: impl Into<String>) -> Reply {
+    Reply::Error(ReplyError { code: "invalid_request".to_string(), message: message.into() })
+}
+
+fn encryption(backend: &dyn Backend, key: Option<EncryptionKey>) -> Reply {
+    let encrypted = key.is_some();
+    match backend.set_encryption(key) {
+        Ok(()) => Reply::Encrypted(encrypted),
+        Err(message) => Reply::Error(ReplyError { code: "encryption_failed".to_string(), message }),
+    }
+}
+
+/// Answer one request line. Subscriptions need the connection, so `serve` handles those.
+pub fn handle(backend: &dyn Backend, line: &str) -> Reply {
+    match serde_json::from_str(line) {
+        Ok(Request::Action(action)) => match backend.dispatch(action) {
+            Ok(state) => Reply::State(state),
+            Err(error) => Reply::Error(error.into()),
+        },
+        Ok(Request::Query(Query::State)) => Reply::State(backend.state()),
+        Ok(Request::Query(Query::History { from, to })) => Reply::History(backend.history(&from, &to)),
+        Ok(Request::Subscribe { subscribe }) => Reply::Subscribed(subscribe),
+        Ok(Request::Flush { flush }) => {
+            if flush {
+                backend.flush();
+            }
+            Reply::Flushed(flush)
+        }
+        Ok(Request::Import { import, format }) => match backend.import(&import, &format) {
+            Ok(summary) => Reply::Imported(summary),
+            Err(message) => Reply::Error(ReplyError { code: "import_failed".to_string(), message }),
+        },
+        Ok(Request::Encrypt { encrypt }) => encryption(backend, Some(encrypt)),
+        Ok(Request::Decrypt { decrypt: true }) => encryption(backend, None),
+        Ok(Request::Decrypt { decrypt: false }) => Reply::Encrypted(false),
+        Err(_) => invalid(format!("not a request: {}", line)),
+    }
+}
+
+fn write_line(mut conn: &Stream, value: &impl Serialize) -> io::Result<()> {
+    let mut line = serde_json::to_vec(value).map_err(io::Error::other)?;
+    line.push(b'\n');
+    conn.write_all(&line)
+}
+
+fn serve(conn: Stream, backend: &dyn Backend, subscribers: &Subscribers) -> io::Result<()> {
+    let mut reader = BufReader::new(&conn);
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 0 {
+        let request = line.trim();
+        if !request.is_empty() {
+            if let Ok(Request::Subscribe { subscribe: true }) = serde_json::from_str(request) {
+                // Subscribed before replying, so no event after the reply is missed
+                let events = subscribers.subscribe();
+                write_line(&conn, &Reply::Subscribed(true))?;
+                for event in events {
+                    (&conn).write_all(format!("{}\n", event).as_bytes())?;
+                }
+                return Ok(());
+            }
+            write_line(&conn, &handle(backend, request))?;
+        }
+        line.clear();
+    }
+    Ok(())
+}
+
+// Another instance still answering keeps its socket; a file left behind by a crash is replaced
+fn bind(dir: &Path) -> io::Result<Listener> {
+    let create = || ListenerOptions::new().name(socket_name(dir)?).create_sync();
+    match create() {
+        Err(e) if e.kind() == io::ErrorKind::AddrInUse => {
+            if Stream::connect(socket_name(dir)?).is_ok() {
+                return Err(e);
+            }
+            #[cfg(unix)]
+            let _ = std::fs::remove_file(dir.join(SOCKET_FILE));
+            create()
+        }
+        result => result,
+    }
+}
+
+/// Accept connections on the socket for `dir`, each on its own thread. Fails when the socket
+/// cannot be created, e.g. because another instance is already listening.
+pub fn listen(dir: &Path, backend: Arc<dyn Backend>, subscribers: &'static Subscribers) -> io::Result<()> {
+    let listener = bind(dir)?;
+    std::thread::spawn(move || {
+        for conn in listener.incoming() {
+            let conn = match conn {
+                Ok(conn) => conn,
+                Err(e) => {
+                    eprintln!("Control socket connection failed: {}", e);
+                    continue;
+                }
+            };
+            let backend = backend.clone();
+            std::thread::spawn(move || {
+                // Usually the other side hanging up
+                let _ = serve(conn, &*backend, subscribers);
+            });
+        }
+    });
+    Ok(())
+}
+
+/// A connection to the running app.
+pub struct Client {
+    conn: BufReader<Stream>,
+}
+
+impl Client {
+    /// Fails when no app is listening for `dir`.
+    pub fn connect(dir: &Path) -> io::Result<Self> {
+        Ok(Self { conn: BufReader::new(Stream::connect(socket_name(dir)?)?) })
+    }
+
+    pub fn request(&mut self, request: &Request) -> io::Result<Reply> {
+        write_line(self.conn.get_ref(), request)?;
+        self.read()?.ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "the app closed the connection"))
+    }
+
+    /// The next event after a subscription; `None` once the app has gone away.
+    pub fn next_event(&mut self) -> io::Result<Option<Event>> {
+        self.read()
+    }
+
+    fn read<T: for<'de> Deserialize<'de>>(&mut self) -> io::Result<Option<T>> {
+        let mut line = String::new();
+        if self.conn.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        serde_json::from_str(&line).map(Some).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::action;
+    use crate::testing::TempDir;
+    use crate::timer::Status;
+
+    struct Timer(Mutex<TimerState>);
+
+    impl Timer {
+        fn new() -> Self {
+            let mut state = TimerState::default();
+            state.reset();
+            Self(Mutex::new(state))
+        }
+    }
+
+    impl Backend for Timer {
+        fn dispatch(&self, action: Action) -> Result<TimerState, ActionError> {
+            let mut state = self.0.lock().unwrap();
+            action::apply(&mut state, action)?;
+            Ok(state.clone())
+        }
+        fn state(&self) -> TimerState {
+            self.0.lock().unwrap().clone()
+        }
+        fn history(&self, from: &str, _to: &str) -> Vec<HistoryEntry> {
+            vec![HistoryEntry { date: from.to_string(), completed: 2, forfeited: 0, total_focus_minutes: 50 }]
+        }
+        fn flush(&self) {}
+        fn import(&self, path: &Path, format: &ImportFormat) -> Result<ImportSummary, String> {
+            match format {
+                ImportFormat::Toggl => Ok(ImportSummary { days_added: 1, ..ImportSummary::default() }),
+                _ => Err(format!("Could not read {}", path.display())),
+            }
+        }
+        fn set_encryption(&self, key: Option<EncryptionKey>) -> Result<(), String> {
+            match key {
+                Some(EncryptionKey::Passphrase { env }) => Err(format!("{} is not set", env)),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    fn code(reply: Reply) -> String {
+        match reply {
+            Reply::Error(error) => error.code,
+            other => panic!("expected an error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_handle() {
+        let timer = Timer::new();
+        assert_eq!(code(handle(&timer, r#"{"action":"pause"}"#)), "not_running");
+        assert_eq!(code(handle(&timer, r#"{"action":"sleep"}"#)), "invalid_request");
+        assert_eq!(code(handle(&timer, "pause")), "invalid_request");
+
+        let Reply::State(state) = handle(&timer, r#"{"action":"start_focus","duration":600}"#) else { panic!() };
+        assert_eq!((&state.status, state.total), (&Status::Focus, 600));
+        assert_eq!(handle(&timer, r#"{"query":"state"}"#), Reply::State(state));
+
+        let Reply::History(entries) = handle(&timer, r#"{"query":"history","from":"2026-03-01","to":"2026-03-07"}"#) else {
+            panic!()
+        };
+        assert_eq!(entries[0].date, "2026-03-01");
+        assert_eq!(
+            serde_json::to_value(handle(&timer, r#"{"subscribe":false}"#)).unwrap(),
+            serde_json::json!({ "subscribed": false })
+        );
+        assert_eq!(handle(&timer, r#"{"flush":true}"#), Reply::Flushed(true));
+
+        let Reply::Imported(summary) = handle(&timer, r#"{"import":"toggl.csv","format":"toggl"}"#) else { panic!() };
+        assert_eq!(summary.days_added, 1);
+        assert_eq!(code(handle(&timer, r#"{"import":"week.csv","format":"csv","start":"When"}"#)), "import_failed");
+        let request = Request::Import { import: PathBuf::from("week.csv"), format: ImportFormat::Jsonl };
+        assert_eq!(serde_json::to_value(&request).unwrap(), serde_json::json!({ "import": "week.csv", "format": "jsonl" }));
+
+        assert_eq!(handle(&timer, r#"{"encrypt":{"source":"keyfile","path":"/keys/pomodoro"}}"#), Reply::Encrypted(true));
+        assert_eq!(code(handle(&timer, r#"{"encrypt":{"source":"passphrase"}}"#)), "encryption_failed");
+        assert_eq!(handle(&timer, r#"{"decrypt":true}"#), Reply::Encrypted(false));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_socket_round_trip() {
+        static SUBSCRIBERS: Subscribers = Subscribers::new();
+        let dir = TempDir::new("control");
+        // Left behind by a crash
+        std::fs::write(dir.join(SOCKET_FILE), "").unwrap();
+
+        listen(&dir, Arc::new(Timer::new()), &SUBSCRIBERS).unwrap();
+        // A second instance leaves the live socket alone
+        let second = listen(&dir, Arc::new(Timer::new()), &SUBSCRIBERS).unwrap_err();
+        assert_eq!(second.kind(), io::ErrorKind::AddrInUse);
+
+        let mut watcher = Client::connect(&dir).unwrap();
+        let subscribe = Request::Subscribe { subscribe: true };
+        assert_eq!(watcher.request(&subscribe).unwrap(), Reply::Subscribed(true));
+
+        let mut client = Client::connect(&dir).unwrap();
+        let start = Request::Action(Action::StartFocus { duration: None, label: None });
+        assert!(matches!(client.request(&start).unwrap(), Reply::State(state) if state.status == Status::Focus));
+        let Reply::State(state) = client.request(&Request::Query(Query::State)).unwrap() else { panic!() };
+        assert_eq!(state.status, Status::Focus);
+
+        SUBSCRIBERS.publish(&LifecycleEvent::MicroBreak { elapsed: 300 });
+        let (sender, received) = mpsc::channel();
+        std::thread::spawn(move || sender.send(watcher.next_event().unwrap()));
+        let event = received.recv_timeout(Duration::from_secs(5)).unwrap().unwrap();
+        assert_eq!(event, Event { event: "micro-break".to_string(), data: serde_json::json!({ "elapsed": 300 }) });
+    }
+}
@@ -0,0 +1,360 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::MutexGuard;
+use crate::action::{self, Action, ActionError};
+use crate::events::{self, LifecycleEvent};
+use crate::persistence::{self, Phase, SessionRecord};
+use crate::scheduler::Waker;
+use crate::shared::Shared;
+use crate::timer::TimerState;
+
+/// Everything the controller does outside the timer: disk, history, tray, windows, webviews.
+/// The app implements it on top of Tauri; tests record the calls.
+pub trait Shell: Send + Sync {
+    /// `now` writes through (transitions); otherwise the write waits for the next checkpoint (ticks).
+    fn save_state(&self, state: &TimerState, now: bool);
+    /// The last state saved, to recover from a panic that left the timer half-updated.
+    fn load_state(&self) -> TimerState;
+    fn log_session(&self, record: SessionRecord);
+    fn emit(&self, event: &LifecycleEvent);
+    /// The per-second `timer-tick`.
+    fn tick(&self, state: &TimerState);
+    /// Rebuild the tray menu. Closes an open dropdown, so only on transitions.
+    fn update_menu(&self, state: &TimerState);
+    /// Title, icon and tooltip; cheap enough for every tick.
+    fn refresh_tray(&self, state: &TimerState);
+    fn show_sanctuary(&self, show: bool);
+    fn show_micro_break(&self);
+}
+
+/// Owns the timer. Tray clicks, menu items, shortcuts, commands, the tick and the state.json
+/// watcher all come through here. The lock is held while the state is saved, so writes reach
+/// disk in the order the changes were made, and never while the rest of the shell runs.
+pub struct Controller {
+    timer: Shared<TimerState>,
+    shell: Box<dyn Shell>,
+    waker: Waker,
+    /// Set when the timer was restored after a panic; the next publish rebuilds the menu.
+    restored: AtomicBool,
+}
+
+impl Controller {
+    pub fn new(state: TimerState, shell: Box<dyn Shell>) -> Self {
+        Self { timer: Shared::new(state), shell, waker: Waker::default(), restored: AtomicBool::new(false) }
+    }
+
+    /// A panic while the timer was locked leaves it possibly half-updated: go back to the
+    /// last saved state, keeping the durations from settings.
+    fn lock(&self) -> MutexGuard<'_, TimerState> {
+        self.timer.lock(|poisoned| {
+            eprintln!("Timer state was poisoned by a panic; restoring the last saved state");
+            self.restored.store(true, Ordering::Relaxed);
+            let mut state = self.shell.load_state();
+            state.config = poisoned.config.clone();
+            state
+        })
+    }
+
+    /// Woken on every change outside `tick`, so the scheduler can re-plan its next deadline.
+    pub fn waker(&self) -> Waker {
+        self.waker.clone()
+    }
+
+    pub fn state(&self) -> TimerState {
+        self.lock().clone()
+    }
+
+    /// Validate and apply `action`, then save, log and publish the transition.
+    pub fn dispatch(&self, action: Action) -> Result<TimerState, ActionError> {
+        let mut state = self.lock();
+        let before = state.clone();
+        let effects = action::apply(&mut state, action)?;
+        let after = state.clone();
+        self.shell.save_state(&after, true);
+        drop(state);
+
+        self.waker.wake();
+        self.publish(&before, &after, effects.log);
+        if let Some(show) = effects.sanctuary {
+            self.shell.show_sanctuary(show);
+        }
+        Ok(after)
+    }
+
+    /// Bring the timer up to the wall clock (the scheduler calls this at each deadline):
+    /// micro-breaks, and finishing a session. A finished focus session goes straight into
+    /// its suggested break in the Sanctuary.
+    pub fn tick(&self) -> TimerState {
+        let mut state = self.lock();
+        let before = state.clone();
+        let mut records = Vec::new();
+        let finished = state.calculate_remaining(false);
+        // After the update, so a session that just ended gets no micro-break
+        let micro_break = state.should_trigger_micro_break();
+        if let Some((was_focus, minutes)) = finished {
+            if was_focus {
+                // The capture note belongs to the focus session, not to a break
+                let (label, tags) = state.take_capture();
+                records.push(persistence::session_record(Phase::Focus, true, minutes, label, tags));
+                state.start_break_now();
+            } else {
+                records.push(persistence::session_record(Phase::Break, true, minutes, None, Vec::new()));
+            }
+        }
+        let after = state.clone();
+        self.shell.save_state(&after, finished.is_some());
+        drop(state);
+
+        self.shell.tick(&after);
+        self.publish(&before, &after, records);
+        if micro_break {
+            self.shell.show_micro_break();
+        }
+        if matches!(finished, Some((true, _))) {
+            self.shell.show_sanctuary(true);
+        }
+        after
+    }
+
+    /// Replace the timer with what `load` returns, if anything: state.json written by someone
+    /// else. Returns whether it was replaced.
+    pub fn adopt(&self, load: impl FnOnce(&TimerState) -> Option<TimerState>) -> bool {
+        let mut state = self.lock();
+        let Some(external) = load(&state) else { return false };
+        let before = std::mem::replace(&mut *state, external);
+        let after = state.clone();
+        drop(state);
+
+        self.waker.wake();
+        self.publish(&before, &after, Vec::new());
+        self.shell.tick(&after);
+        true
+    }
+
+    /// Changes that are not timer actions (settings, capture notes). Saved and shown in the
+    /// tray title; rebuilding the menu is up to the caller.
+    pub fn update(&self, change: impl FnOnce(&mut TimerState)) -> TimerState {
+        let mut state = self.lock();
+        change(&mut state);
+        let after = state.clone();
+        self.shell.save_state(&after, true);
+        drop(state);
+
+        self.waker.wake();
+        self.shell.refresh_tray(&after);
+        after
+    }
+
+    /// The one place state changes are published: history, lifecycle events, then the tray.
+    /// The menu is only rebuilt when something it shows changed, so ticks and micro-breaks
+    /// never close an open dropdown.
+    fn publish(&self, before: &TimerState, after: &TimerState, records: Vec<SessionRecord>) {
+        let lifecycle = events::between(before, after, &records);
+        for record in records {
+            self.shell.log_session(record);
+        }
+        for event in &lifecycle {
+            self.shell.emit(event);
+        }
+        // Cleared on every publish, not only when nothing else changed
+        let restored = self.restored.swap(false, Ordering::Relaxed);
+        let menu_changed = restored
+            || before.status != after.status
+            || before.timer_type != after.timer_type
+            || before.extensions != after.extensions
+            || before.sessions_today != after.sessions_today;
+        if menu_changed {
+            self.shell.update_menu(after);
+        }
+        self.shell.refresh_tray(after);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::{Arc, Mutex};
+    use crate::timer::{Status, TimerType};
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl Recorder {
+        fn take(&self) -> Vec<String> {
+            std::mem::take(&mut *self.0.lock().unwrap())
+        }
+        fn push(&self, call: String) {
+            self.0.lock().unwrap().push(call);
+        }
+    }
+
+    impl Shell for Arc<Recorder> {
+        fn save_state(&self, _state: &TimerState, now: bool) {
+            self.push(format!("save now={}", now));
+        }
+        fn load_state(&self) -> TimerState {
+            self.push("load".to_string());
+            let mut state = TimerState::default();
+            state.reset();
+            state
+        }
+        fn log_session(&self, record: SessionRecord) {
+            self.push(format!("log {} {}", record.phase.as_str(), record.completed));
+        }
+        fn emit(&self, event: &LifecycleEvent) {
+            self.push(event.name().to_string());
+        }
+        fn tick(&self, _state: &TimerState) {
+            self.push("tick".to_string());
+        }
+        fn update_menu(&self, _state: &TimerState) {
+            self.push("menu".to_string());
+        }
+        fn refresh_tray(&self, _state: &TimerState) {
+            self.push("tray".to_string());
+        }
+        fn show_sanctuary(&self, show: bool) {
+            self.push(format!("sanctuary {}", show));
+        }
+        fn show_micro_break(&self) {
+            self.push("micro-break".to_string());
+        }
+    }
+
+    fn controller() -> (Controller, Arc<Recorder>) {
+        let mut state = TimerState::default();
+        state.reset();
+        let recorder = Arc::new(Recorder::default());
+        (Controller::new(state, Box::new(recorder.clone())), recorder)
+    }
+
+    #[test]
+    fn test_dispatch_saves_and_publishes() {
+        let (controller, calls) = controller();
+        controller.dispatch(Action::StartFocus { duration: None, label: None }).unwrap();
+        assert_eq!(calls.take(), vec!["save now=true", "session-started", "menu", "tray"]);
+
+        // Rejected actions have no side effects
+        assert!(controller.dispatch(Action::Resume).is_err());
+        assert!(calls.take().is_empty());
+
+        controller.dispatch(Action::StartCalm { duration: None }).unwrap();
+        assert_eq!(
+            calls.take(),
+            vec!["save now=true", "log focus false", "session-forfeited", "break-started", "menu", "tray", "sanctuary true"]
+        );
+        assert_eq!(controller.state().timer_type, TimerType::Calm);
+    }
+
+    #[test]
+    fn test_tick_finishes_into_break() {
+        let (controller, calls) = controller();
+        controller.dispatch(Action::StartFocus { duration: None, label: None }).unwrap();
+        calls.take();
+
+        // A plain tick never rebuilds the menu
+        controller.tick();
+        assert_eq!(calls.take(), vec!["save now=false", "tick", "tray"]);
+
+        controller.update(|state| state.start_time = state.start_time.map(|t| t - state.total));
+        calls.take();
+        let state = controller.tick();
+        assert_eq!((state.status, state.sessions_today), (Status::Break, 1));
+        assert_eq!(
+            calls.take(),
+            vec!["save now=true", "tick", "log focus true", "session-completed", "break-started", "menu", "tray", "sanctuary true"]
+        );
+    }
+
+    #[test]
+    fn test_adopt_external_state() {
+        let (controller, calls) = controller();
+        assert!(!controller.adopt(|_| None));
+        assert!(calls.take().is_empty());
+
+        let mut external = controller.state();
+        external.start_focus(600);
+        assert!(controller.adopt(|_| Some(external.clone())));
+        assert_eq!(calls.take(), vec!["session-started", "menu", "tray", "tick"]);
+        assert_eq!(controller.state(), external);
+    }
+
+    #[test]
+    fn test_recovers_from_panic() {
+        let (controller, calls) = controller();
+        controller.dispatch(Action::StartFocus { duration: None, label: None }).unwrap();
+        let _ = std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    let mut state = controller.lock();
+                    state.config.focus = 42;
+                    state.status = Status::Paused;
+                    panic!("half-way through a change");
+                })
+                .join()
+        });
+        calls.take();
+
+        // Back to the saved state, config kept, and the menu redrawn even though the status matches
+        let state = controller.state();
+        assert_eq!((state.status, state.config.focus), (Status::Idle, 42));
+        assert_eq!(calls.take(), vec!["load"]);
+        controller.tick();
+        assert_eq!(calls.take(), vec!["save now=false", "tick", "menu", "tray"]);
+        controller.tick();
+        assert_eq!(calls.take(), vec!["save now=false", "tick", "tray"]);
+    }
+
+    #[test]
+    fn test_restored_flag_clears_with_a_transition() {
+        let (controller, calls) = controller();
+        let _ = std::thread::scope(|scope| scope.spawn(|| {
+            let _state = controller.lock();
+            panic!("while holding the lock");
+        }).join());
+        // The restore and a status change in the same publish: one rebuild, then none
+        controller.dispatch(Action::StartFocus { duration: None, label: None }).unwrap();
+        calls.take();
+        controller.tick();
+        assert_eq!(calls.take(), vec!["save now=false", "tick", "tray"]);
+    }
+
+    // Saves only, and the first one is slow
+    #[derive(Default)]
+    struct SlowDisk(Mutex<Vec<Status>>, AtomicUsize);
+
+    impl Shell for Arc<SlowDisk> {
+        fn save_state(&self, state: &TimerState, _now: bool) {
+            if self.1.fetch_add(1, Ordering::Relaxed) == 0 {
+                std::thread::sleep(std::time::Duration::from_millis(100));
+            }
+            self.0.lock().unwrap().push(state.status.clone());
+        }
+        fn load_state(&self) -> TimerState {
+            TimerState::default()
+        }
+        fn log_session(&self, _record: SessionRecord) {}
+        fn emit(&self, _event: &LifecycleEvent) {}
+        fn tick(&self, _state: &TimerState) {}
+        fn update_menu(&self, _state: &TimerState) {}
+        fn refresh_tray(&self, _state: &TimerState) {}
+        fn show_sanctuary(&self, _show: bool) {}
+        fn show_micro_break(&self) {}
+    }
+
+    #[test]
+    fn test_saves_in_order() {
+        let mut state = TimerState::default();
+        state.reset();
+        let disk = Arc::new(SlowDisk::default());
+        let controller = Controller::new(state, Box::new(disk.clone()));
+        std::thread::scope(|scope| {
+            scope.spawn(|| controller.dispatch(Action::StartFocus { duration: None, label: None }).unwrap());
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            // Waits for the start to be on disk instead of being overwritten by it
+            scope.spawn(|| controller.dispatch(Action::Pause).unwrap());
+        });
+        assert_eq!(*disk.0.lock().unwrap(), vec![Status::Focus, Status::Paused]);
+        assert_eq!(controller.state().status, Status::Paused);
+    }
+}
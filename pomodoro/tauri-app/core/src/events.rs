@@ -0,0 +1,147 @@
+use serde::Serialize;
+use crate::persistence::{Phase, SessionRecord};
+use crate::timer::{Status, TimerState, TimerType};
+
+/// What changed in one transition. Emitted under `name()` with the variant's fields as payload,
+/// alongside the per-second `timer-tick`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum LifecycleEvent {
+    SessionStarted { duration: u64, label: Option<String> },
+    SessionPaused { kind: TimerType, remaining: u64 },
+    SessionResumed { kind: TimerType, remaining: u64 },
+    SessionCompleted { minutes: u32, label: Option<String>, tags: Vec<String>, sessions_today: u32 },
+    SessionForfeited { minutes: u32, label: Option<String>, reason: Option<String> },
+    BreakStarted { kind: TimerType, duration: u64 },
+    BreakEnded { minutes: u32, skipped: bool },
+    /// Seconds into the focus session.
+    MicroBreak { elapsed: u64 },
+    DayRolledOver { date: String, previous: String },
+}
+
+impl LifecycleEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LifecycleEvent::SessionStarted { .. } => "session-started",
+            LifecycleEvent::SessionPaused { .. } => "session-paused",
+            LifecycleEvent::SessionResumed { .. } => "session-resumed",
+            LifecycleEvent::SessionCompleted { .. } => "session-completed",
+            LifecycleEvent::SessionForfeited { .. } => "session-forfeited",
+            LifecycleEvent::BreakStarted { .. } => "break-started",
+            LifecycleEvent::BreakEnded { .. } => "break-ended",
+            LifecycleEvent::MicroBreak { .. } => "micro-break",
+            LifecycleEvent::DayRolledOver { .. } => "day-rolled-over",
+        }
+    }
+}
+
+fn running(state: &TimerState) -> bool {
+    matches!(state.status, Status::Focus | Status::Break)
+}
+
+/// The events between `before` and `after`, given the sessions recorded on the way, in the
+/// order they happened: day rollover, what ended, pause/resume, what started, micro-break.
+pub fn between(before: &TimerState, after: &TimerState, records: &[SessionRecord]) -> Vec<LifecycleEvent> {
+    let mut events = Vec::new();
+    if !before.last_date.is_empty() && before.last_date != after.last_date {
+        events.push(LifecycleEvent::DayRolledOver { date: after.last_date.clone(), previous: before.last_date.clone() });
+    }
+
+    for record in records {
+        events.push(match (record.phase, record.completed) {
+            (Phase::Focus, true) => LifecycleEvent::SessionCompleted {
+                minutes: record.minutes,
+                label: record.label.clone(),
+                tags: record.tags.clone(),
+                sessions_today: after.sessions_today,
+            },
+            (Phase::Focus, false) => LifecycleEvent::SessionForfeited {
+                minutes: record.minutes,
+                label: record.label.clone(),
+                reason: record.reason.clone(),
+            },
+            (phase, _) => LifecycleEvent::BreakEnded { minutes: record.minutes, skipped: phase == Phase::SkippedBreak },
+        });
+    }
+
+    let same_session = before.timer_type == after.timer_type;
+    if running(before) && after.status == Status::Paused && same_session {
+        events.push(LifecycleEvent::SessionPaused { kind: after.timer_type.clone(), remaining: after.remaining });
+    } else if before.status == Status::Paused && running(after) && same_session {
+        events.push(LifecycleEvent::SessionResumed { kind: after.timer_type.clone(), remaining: after.remaining });
+    } else if running(after) && (!running(before) || !same_session || before.start_time != after.start_time) {
+        events.push(match after.timer_type {
+            TimerType::Focus => LifecycleEvent::SessionStarted { duration: after.total, label: after.label.clone() },
+            _ => LifecycleEvent::BreakStarted { kind: after.timer_type.clone(), duration: after.total },
+        });
+    }
+
+    if after.last_micro_break > 0 && after.last_micro_break != before.last_micro_break {
+        events.push(LifecycleEvent::MicroBreak { elapsed: after.last_micro_break });
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence;
+
+    fn idle() -> TimerState {
+        let mut state = TimerState { last_date: "2026-03-01".to_string(), ..Default::default() };
+        state.reset();
+        state
+    }
+
+    #[test]
+    fn test_start_pause_resume() {
+        let before = idle();
+        let mut after = before.clone();
+        after.start_focus(1500);
+        assert_eq!(between(&before, &after, &[]), vec![LifecycleEvent::SessionStarted { duration: 1500, label: None }]);
+
+        let mut paused = after.clone();
+        paused.status = Status::Paused;
+        paused.start_time = None;
+        assert_eq!(between(&after, &paused, &[])[0].name(), "session-paused");
+        assert_eq!(between(&paused, &after, &[])[0].name(), "session-resumed");
+
+        // A plain tick is not an event
+        let mut ticked = after.clone();
+        ticked.remaining -= 1;
+        assert!(between(&after, &ticked, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_completion_starts_break_and_rolls_over() {
+        let mut before = idle();
+        before.start_focus(1500);
+        let mut after = before.clone();
+        after.sessions_today = 1;
+        after.last_date = "2026-03-02".to_string();
+        after.start_break(300);
+        let record = persistence::session_record(Phase::Focus, true, 25, Some("Essay".to_string()), Vec::new());
+
+        let names: Vec<&str> = between(&before, &after, &[record]).iter().map(LifecycleEvent::name).collect();
+        assert_eq!(names, vec!["day-rolled-over", "session-completed", "break-started"]);
+
+        let skipped = persistence::session_record(Phase::SkippedBreak, false, 2, None, Vec::new());
+        assert_eq!(
+            between(&after, &idle(), &[skipped]),
+            vec![
+                LifecycleEvent::DayRolledOver { date: "2026-03-01".to_string(), previous: "2026-03-02".to_string() },
+                LifecycleEvent::BreakEnded { minutes: 2, skipped: true },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_micro_break() {
+        let mut before = idle();
+        before.start_focus(1500);
+        let mut after = before.clone();
+        after.last_micro_break = 300;
+        assert_eq!(between(&before, &after, &[]), vec![LifecycleEvent::MicroBreak { elapsed: 300 }]);
+        assert_eq!(serde_json::to_value(&between(&before, &after, &[])[0]).unwrap(), serde_json::json!({ "elapsed": 300 }));
+    }
+}
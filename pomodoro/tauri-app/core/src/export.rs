@@ -0,0 +1,319 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use chrono::{Local, TimeZone, Utc};
+use serde::{Serialize, Deserialize};
+use crate::persistence::{self, History, HistoryEntry, SessionRecord};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Jsonl,
+    Ics,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Jsonl => "jsonl",
+            ExportFormat::Ics => "ics",
+        }
+    }
+}
+
+/// Which part of history to export. Empty fields mean "everything".
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportFilter {
+    pub from: Option<String>,  // YYYY-MM-DD, inclusive
+    pub to: Option<String>,
+    pub tags: Vec<String>,     // any of
+}
+
+/// One exported row: a session, or a whole day for history recorded before
+/// per-session records existed. This is also the JSON Lines format.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum ExportItem {
+    Session(SessionRecord),
+    Day(HistoryEntry),
+}
+
+/// Lowercase, no `#`, no commas or whitespace.
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim()
+        .trim_start_matches('#')
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != ',')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+impl ExportFilter {
+    fn includes_date(&self, date: &str) -> bool {
+        self.from.as_deref().is_none_or(|from| date >= from)
+            && self.to.as_deref().is_none_or(|to| date <= to)
+    }
+
+    fn includes_tags(&self, tags: &[String]) -> bool {
+        self.tags.is_empty()
+            || self.tags.iter().any(|wanted| tags.iter().any(|t| *t == normalize_tag(wanted)))
+    }
+}
+
+/// Sessions where we have them, daily aggregates for days we don't, oldest first.
+pub fn select(history: &History, filter: &ExportFilter) -> Vec<ExportItem> {
+    let mut items: Vec<(String, u64, ExportItem)> = Vec::new();
+    for record in &history.records {
+        if filter.includes_date(&record.date) && filter.includes_tags(&record.tags) {
+            items.push((record.date.clone(), record.ended_at, ExportItem::Session(record.clone())));
+        }
+    }
+    // Days carry no tags, so a tag filter leaves them out.
+    if filter.tags.is_empty() {
+        for day in &history.sessions {
+            let has_records = history.records.iter().any(|r| r.date == day.date);
+            if !has_records && filter.includes_date(&day.date) {
+                items.push((day.date.clone(), 0, ExportItem::Day(day.clone())));
+            }
+        }
+    }
+    items.sort_by(|a, b| (&a.0, a.1).cmp(&(&b.0, b.1)));
+    items.into_iter().map(|(_, _, item)| item).collect()
+}
+
+fn local_time(timestamp: u64) -> String {
+    Local
+        .timestamp_opt(timestamp as i64, 0)
+        .single()
+        .map(|t| t.format("%Y-%m-%dT%H:%M:%S%:z").to_string())
+        .unwrap_or_default()
+}
+
+pub fn to_csv(items: &[ExportItem]) -> String {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(["date", "kind", "started_at", "ended_at", "minutes", "completed", "forfeited", "label", "tags", "phase"])
+        .expect("Could not write CSV");
+    for item in items {
+        let row = match item {
+            ExportItem::Session(s) => {
+                let (completed, forfeited, _) = s.day_totals();
+                [
+                    s.date.clone(),
+                    "session".to_string(),
+                    local_time(s.ended_at.saturating_sub(s.minutes as u64 * 60)),
+                    local_time(s.ended_at),
+                    s.minutes.to_string(),
+                    completed.to_string(),
+                    forfeited.to_string(),
+                    s.label.clone().unwrap_or_default(),
+                    s.tags.join(" "),
+                    s.phase.as_str().to_string(),
+                ]
+            }
+            ExportItem::Day(d) => [
+                d.date.clone(),
+                "day".to_string(),
+                String::new(),
+                String::new(),
+                d.total_focus_minutes.to_string(),
+                d.completed.to_string(),
+                d.forfeited.to_string(),
+                String::new(),
+                String::new(),
+                String::new(),
+            ],
+        };
+        writer.write_record(&row).expect("Could not write CSV");
+    }
+    String::from_utf8(writer.into_inner().expect("Could not write CSV")).expect("CSV is UTF-8")
+}
+
+pub fn to_jsonl(items: &[ExportItem]) -> String {
+    items
+        .iter()
+        .map(|item| serde_json::to_string(item).expect("Could not serialize export") + "\n")
+        .collect()
+}
+
+fn ics_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn ics_time(timestamp: u64) -> String {
+    Utc.timestamp_opt(timestamp as i64, 0)
+        .single()
+        .map(|t| t.format("%Y%m%dT%H%M%SZ").to_string())
+        .unwrap_or_default()
+}
+
+/// Split a content line into 75-octet pieces joined by CRLF and a space, never inside a character (RFC 5545 3.1).
+fn ics_fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            // The leading space counts toward the continuation line
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
+
+/// Completed focus sessions as calendar events; breaks are left out. Days without session times are skipped.
+pub fn to_ics(items: &[ExportItem]) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Break Sanctuary//Pomodoro//EN".to_string(),
+    ];
+    for item in items {
+        let ExportItem::Session(s) = item else { continue };
+        if !s.completed || !s.phase.is_focus() {
+            continue;
+        }
+        let summary = match &s.label {
+            Some(label) => format!("Focus: {}", label),
+            None => "Focus".to_string(),
+        };
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}-{}@pomodoro.native", s.ended_at, s.minutes));
+        lines.push(format!("DTSTAMP:{}", ics_time(s.ended_at)));
+        lines.push(format!("DTSTART:{}", ics_time(s.ended_at.saturating_sub(s.minutes as u64 * 60))));
+        lines.push(format!("DTEND:{}", ics_time(s.ended_at)));
+        lines.push(format!("SUMMARY:{}", ics_escape(&summary)));
+        if !s.tags.is_empty() {
+            lines.push(format!("CATEGORIES:{}", s.tags.iter().map(|t| ics_escape(t)).collect::<Vec<_>>().join(",")));
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| ics_fold(line) + "\r\n").collect()
+}
+
+pub fn render(items: &[ExportItem], format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Csv => to_csv(items),
+        ExportFormat::Jsonl => to_jsonl(items),
+        ExportFormat::Ics => to_ics(items),
+    }
+}
+
+/// `~/.pomodoro/exports/pomodoro-<today>.<ext>`
+pub fn default_path(format: ExportFormat) -> PathBuf {
+    let mut path = persistence::get_pomodoro_dir();
+    path.push("exports");
+    path.push(format!("pomodoro-{}.{}", Local::now().format("%Y-%m-%d"), format.extension()));
+    path
+}
+
+/// Export the stored history to `path`, returning the number of items written.
+pub fn export_to(path: &Path, format: ExportFormat, filter: &ExportFilter) -> io::Result<usize> {
+    let items = select(&persistence::load_history(), filter);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, render(&items, format))?;
+    Ok(items.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::Phase;
+
+    fn history() -> History {
+        let mut history = History::default();
+        history.sessions.push(HistoryEntry {
+            date: "2025-12-01".to_string(),
+            completed: 3,
+            forfeited: 1,
+            total_focus_minutes: 75,
+        });
+        history.apply(&SessionRecord {
+            ended_at: 1_769_940_000,
+            label: Some("Write, then edit".to_string()),
+            tags: vec!["writing".to_string()],
+            ..SessionRecord::focus("2026-02-01", 25)
+        });
+        history.apply(&SessionRecord { ended_at: 1_769_943_000, completed: false, ..SessionRecord::focus("2026-02-01", 10) });
+        history.apply(&SessionRecord {
+            ended_at: 1_769_943_600,
+            completed: false,
+            phase: Phase::SkippedBreak,
+            ..SessionRecord::focus("2026-02-01", 3)
+        });
+        history
+    }
+
+    #[test]
+    fn test_select_mixes_sessions_and_legacy_days() {
+        let items = select(&history(), &ExportFilter::default());
+        assert_eq!(items.len(), 4);
+        assert!(matches!(items[0], ExportItem::Day(_)));
+        assert!(matches!(items[1], ExportItem::Session(_)));
+    }
+
+    #[test]
+    fn test_select_filters_by_date_and_tag() {
+        let filter = ExportFilter { from: Some("2026-01-01".to_string()), ..Default::default() };
+        assert_eq!(select(&history(), &filter).len(), 3);
+
+        let filter = ExportFilter { tags: vec!["#Writing".to_string()], ..Default::default() };
+        assert_eq!(select(&history(), &filter).len(), 1);
+    }
+
+    #[test]
+    fn test_jsonl_round_trips() {
+        let items = select(&history(), &ExportFilter::default());
+        let parsed: Vec<ExportItem> = to_jsonl(&items)
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(parsed, items);
+    }
+
+    #[test]
+    fn test_csv_and_ics_output() {
+        let items = select(&history(), &ExportFilter::default());
+        let csv = to_csv(&items);
+        assert_eq!(csv.lines().count(), 5);
+        assert!(csv.contains("\"Write, then edit\""));
+        assert!(csv.lines().last().unwrap().ends_with(",0,0,,,skipped_break"));
+
+        let ics = to_ics(&items);
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 1);
+        assert!(ics.contains("SUMMARY:Focus: Write\\, then edit\r\n"));
+        assert!(ics.contains("DTEND:20260201T100000Z"));
+    }
+
+    #[test]
+    fn test_ics_folds_long_lines() {
+        let mut history = History::default();
+        history.apply(&SessionRecord {
+            ended_at: 1_769_940_000,
+            label: Some("Überarbeitung des Kapitels über Zeitmanagement — zweiter Durchgang, mit Anmerkungen".to_string()),
+            ..SessionRecord::focus("2026-02-01", 25)
+        });
+        let ics = to_ics(&select(&history, &ExportFilter::default()));
+        for line in ics.split("\r\n") {
+            assert!(line.len() <= 75, "{:?} is {} octets", line, line.len());
+        }
+        let summary: String = ics
+            .split("\r\n")
+            .skip_while(|line| !line.starts_with("SUMMARY:"))
+            .take_while(|line| line.starts_with("SUMMARY:") || line.starts_with(' '))
+            .map(|line| line.strip_prefix(' ').unwrap_or(line))
+            .collect();
+        assert_eq!(summary, "SUMMARY:Focus: Überarbeitung des Kapitels über Zeitmanagement — zweiter Durchgang\\, mit Anmerkungen");
+    }
+}
@@ -0,0 +1,146 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Mutex, RwLock};
+use chrono::Local;
+use crate::persistence::{History, HistoryEntry, SessionRecord};
+use crate::store::Store;
+
+enum Job {
+    Log(SessionRecord),
+    Flush(Sender<()>),
+}
+
+/// History held in memory for the tray and the stats window.
+///
+/// Loaded once from the store. `log` updates the copy in memory right away and hands the
+/// write to a background thread, so transitions never wait on disk. Writes happen in order;
+/// `flush` waits for them, and `reload` re-reads the store after something else changed it
+/// (imports, retention, encryption).
+pub struct HistoryService {
+    history: RwLock<History>,
+    writer: Mutex<Sender<Job>>,
+    store: &'static dyn Store,
+}
+
+impl HistoryService {
+    pub fn new(store: &'static dyn Store) -> Self {
+        let (writer, jobs) = mpsc::channel();
+        std::thread::spawn(move || {
+            for job in jobs {
+                match job {
+                    // The store panics on I/O errors; lose the one record, not the writer
+                    Job::Log(record) => {
+                        if panic::catch_unwind(AssertUnwindSafe(|| store.log_session(&record))).is_err() {
+                            eprintln!("Could not record the session ending at {}", record.ended_at);
+                        }
+                    }
+                    Job::Flush(done) => {
+                        let _ = done.send(());
+                    }
+                }
+            }
+        });
+        Self {
+            history: RwLock::new(store.load_history()),
+            writer: Mutex::new(writer),
+            store,
+        }
+    }
+
+    pub fn snapshot(&self) -> History {
+        self.history.read().unwrap().clone()
+    }
+
+    /// Daily entries with `from <= date <= to` (YYYY-MM-DD), oldest first.
+    pub fn between(&self, from: &str, to: &str) -> Vec<HistoryEntry> {
+        let mut entries: Vec<HistoryEntry> = self
+            .history
+            .read()
+            .unwrap()
+            .sessions
+            .iter()
+            .filter(|e| e.date.as_str() >= from && e.date.as_str() <= to)
+            .cloned()
+            .collect();
+        entries.sort_by(|a, b| a.date.cmp(&b.date));
+        entries
+    }
+
+    /// Daily entries for the last `days` days (including today), oldest first.
+    pub fn recent(&self, days: i64) -> Vec<HistoryEntry> {
+        let now = Local::now();
+        let since = (now - chrono::Duration::days(days - 1)).format("%Y-%m-%d").to_string();
+        self.between(&since, &now.format("%Y-%m-%d").to_string())
+    }
+
+    pub fn today(&self) -> Option<HistoryEntry> {
+        self.recent(1).pop()
+    }
+
+    pub fn log(&self, record: SessionRecord) {
+        self.history.write().unwrap().apply(&record);
+        if self.writer.lock().unwrap().send(Job::Log(record)).is_err() {
+            eprintln!("History writer stopped; the session is only kept in memory");
+        }
+    }
+
+    /// Wait until every logged session is on disk (or failed to get there).
+    pub fn flush(&self) {
+        let (done, wait) = mpsc::channel();
+        if self.writer.lock().unwrap().send(Job::Flush(done)).is_ok() {
+            let _ = wait.recv();
+        }
+    }
+
+    pub fn reload(&self) {
+        self.flush();
+        *self.history.write().unwrap() = self.store.load_history();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::JsonStore;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn test_logs_in_memory_then_on_disk() {
+        let dir = TempDir::new("history");
+        let store: &'static JsonStore = Box::leak(Box::new(JsonStore::new(dir.to_path_buf())));
+        let service = HistoryService::new(store);
+
+        service.log(SessionRecord::focus("2026-03-01", 25));
+        service.log(SessionRecord::focus("2026-03-01", 50));
+        service.log(SessionRecord::focus("2026-03-04", 25));
+        let days = service.between("2026-03-01", "2026-03-02");
+        assert_eq!(days.len(), 1);
+        assert_eq!((days[0].completed, days[0].total_focus_minutes), (2, 75));
+
+        service.flush();
+        assert_eq!(store.load_history().records.len(), 3);
+
+        // Something else writes to the store; reload picks it up
+        store.log_session(&SessionRecord::focus("2026-03-02", 15));
+        assert_eq!(service.between("2026-03-01", "2026-03-31").len(), 2);
+        service.reload();
+        assert_eq!(service.between("2026-03-01", "2026-03-31").len(), 3);
+    }
+
+    #[test]
+    fn test_keeps_writing_after_a_failed_write() {
+        let dir = TempDir::new("history-fail");
+        let store: &'static JsonStore = Box::leak(Box::new(JsonStore::new(dir.to_path_buf())));
+        let service = HistoryService::new(store);
+
+        // A history.json that can't be read makes the store panic
+        std::fs::create_dir(dir.join("history.json")).unwrap();
+        service.log(SessionRecord::focus("2026-03-01", 25));
+        service.flush();
+        std::fs::remove_dir(dir.join("history.json")).unwrap();
+        service.log(SessionRecord::focus("2026-03-02", 25));
+        service.flush();
+        assert_eq!(store.load_history().records.len(), 1);
+        assert_eq!(service.between("2026-03-01", "2026-03-31").len(), 2);
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use fluent_langneg::{negotiate_languages, NegotiationStrategy};
+use fluent_syntax::ast;
+use serde::Serialize;
+use unic_langid::LanguageIdentifier;
+
+/// Bundled catalogs as `(locale, Fluent source)`. The first one is the fallback
+/// for locales we don't have and for messages a translation is missing.
+const CATALOGS: &[(&str, &str)] = &[
+    ("en-US", include_str!("../locales/en-US/main.ftl")),
+    ("de", include_str!("../locales/de/main.ftl")),
+];
+
+/// Every message, formatted for the webviews. Variables are left as `{$name}`
+/// for the window scripts to fill in.
+#[derive(Debug, Clone, Serialize)]
+pub struct Catalog {
+    pub locale: String,
+    pub messages: BTreeMap<String, String>,
+}
+
+pub struct Localizer {
+    locale: &'static str,
+    /// The chosen locale first, then the fallback.
+    bundles: Vec<FluentBundle<FluentResource>>,
+}
+
+fn resource(source: &str) -> FluentResource {
+    FluentResource::try_new(source.to_string()).expect("Bundled catalog does not parse")
+}
+
+fn bundle(locale: &str, source: &str) -> FluentBundle<FluentResource> {
+    let id: LanguageIdentifier = locale.parse().expect("Bundled locale is not a valid language tag");
+    let mut bundle = FluentBundle::new_concurrent(vec![id]);
+    // Menu items and window text are plain strings; no bidi isolation marks around values
+    bundle.set_use_isolating(false);
+    bundle.add_resource(resource(source)).expect("Bundled catalog has duplicate messages");
+    bundle
+}
+
+/// The bundled locale that best matches `requested` (`"de-AT"` → `"de"`), or the fallback.
+pub fn negotiate(requested: &str) -> &'static str {
+    let requested: Vec<LanguageIdentifier> = requested.replace('_', "-").parse().into_iter().collect();
+    let available: Vec<LanguageIdentifier> = CATALOGS.iter().map(|(locale, _)| locale.parse().unwrap()).collect();
+    let fallback = available[0].clone();
+    let chosen = negotiate_languages(&requested, &available, Some(&fallback), NegotiationStrategy::Lookup)
+        .first()
+        .map(|id| id.to_string())
+        .unwrap_or_default();
+    CATALOGS
+        .iter()
+        .map(|(locale, _)| *locale)
+        .find(|locale| *locale == chosen)
+        .unwrap_or(CATALOGS[0].0)
+}
+
+/// `None` follows the system locale.
+pub fn resolve(requested: Option<&str>) -> &'static str {
+    let requested = requested
+        .map(str::to_string)
+        .or_else(sys_locale::get_locale)
+        .unwrap_or_default();
+    negotiate(&requested)
+}
+
+impl Localizer {
+    pub fn new(locale: &'static str) -> Self {
+        let mut bundles = Vec::new();
+        for (name, source) in CATALOGS {
+            if *name == locale || *name == CATALOGS[0].0 {
+                bundles.push((*name == locale, bundle(name, source)));
+            }
+        }
+        // Chosen locale before the fallback
+        bundles.sort_by_key(|(chosen, _)| !chosen);
+        Self { locale, bundles: bundles.into_iter().map(|(_, bundle)| bundle).collect() }
+    }
+
+    pub fn locale(&self) -> &'static str {
+        self.locale
+    }
+
+    /// Missing messages fall back to English, then to the message id itself.
+    pub fn format(&self, id: &str, args: Option<&FluentArgs>) -> String {
+        for bundle in &self.bundles {
+            if let Some(pattern) = bundle.get_message(id).and_then(|message| message.value()) {
+                let mut errors = Vec::new();
+                return bundle.format_pattern(pattern, args, &mut errors).into_owned();
+            }
+        }
+        id.to_string()
+    }
+
+    pub fn catalog(&self) -> Catalog {
+        let messages = resource(CATALOGS[0].1)
+            .entries()
+            .filter_map(|entry| match entry {
+                ast::Entry::Message(message) => Some(message.id.name.to_string()),
+                _ => None,
+            })
+            .map(|id| {
+                let text = self.format(&id, None);
+                (id, text)
+            })
+            .collect();
+        Catalog { locale: self.locale.to_string(), messages }
+    }
+}
+
+static CURRENT: RwLock<Option<Arc<Localizer>>> = RwLock::new(None);
+
+fn current() -> Arc<Localizer> {
+    if let Some(localizer) = CURRENT.read().unwrap().as_ref() {
+        return localizer.clone();
+    }
+    set_locale(None);
+    CURRENT.read().unwrap().clone().unwrap()
+}
+
+/// Switch to the best match for the `locale` setting. Returns whether the language changed.
+pub fn set_locale(requested: Option<&str>) -> bool {
+    let locale = resolve(requested);
+    let mut current = CURRENT.write().unwrap();
+    if current.as_ref().is_some_and(|localizer| localizer.locale() == locale) {
+        return false;
+    }
+    *current = Some(Arc::new(Localizer::new(locale)));
+    true
+}
+
+pub fn catalog() -> Catalog {
+    current().catalog()
+}
+
+pub fn tr(id: &str) -> String {
+    current().format(id, None)
+}
+
+pub fn tr_args(id: &str, args: &[(&str, FluentValue)]) -> String {
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, value.clone());
+    }
+    current().format(id, Some(&fluent_args))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiates_bundled_locales() {
+        assert_eq!(negotiate("de-AT"), "de");
+        assert_eq!(negotiate("de_DE"), "de");
+        assert_eq!(negotiate("en-GB"), "en-US");
+        assert_eq!(negotiate("kn-IN"), "en-US");
+        assert_eq!(negotiate("not a locale"), "en-US");
+    }
+
+    #[test]
+    fn test_plurals_and_fallback() {
+        let localizer = Localizer::new("de");
+        let mut args = FluentArgs::new();
+        args.set("count", 1);
+        assert_eq!(localizer.format("menu-sessions-today", Some(&args)), "1 volle Sitzung heute");
+        args.set("count", 3);
+        assert_eq!(localizer.format("menu-sessions-today", Some(&args)), "3 volle Sitzungen heute");
+        assert_eq!(localizer.format("no-such-message", None), "no-such-message");
+
+        let english = Localizer::new("en-US");
+        assert_eq!(english.format("menu-sessions-today", Some(&args)), "3 Full Sessions Today");
+    }
+
+    #[test]
+    fn test_catalog_leaves_variables_for_webviews() {
+        let catalog = Localizer::new("en-US").catalog();
+        assert_eq!(catalog.messages["microbreak-auto-close"], "Auto-closes in {$seconds} seconds");
+        assert_eq!(catalog.messages["sanctuary-label-1"], "Take a breath");
+    }
+
+    #[test]
+    fn test_translations_are_complete() {
+        let english = Localizer::new("en-US").catalog();
+        for (locale, source) in &CATALOGS[1..] {
+            let translated: Vec<String> = resource(source)
+                .entries()
+                .filter_map(|entry| match entry {
+                    ast::Entry::Message(message) => Some(message.id.name.to_string()),
+                    _ => None,
+                })
+                .collect();
+            for id in english.messages.keys() {
+                assert!(translated.contains(id), "{} is missing {}", locale, id);
+            }
+        }
+    }
+
+    #[test]
+    fn test_windows_only_use_known_messages() {
+        let windows = [
+            include_str!("../../src/index.html"),
+            include_str!("../../src/microbreak.html"),
+            include_str!("../../src/settings.html"),
+            include_str!("../../src/stats.html"),
+            include_str!("../../src/capture.html"),
+            include_str!("../../src/main.js"),
+            include_str!("../../src/microbreak.js"),
+            include_str!("../../src/settings.js"),
+            include_str!("../../src/stats.js"),
+        ];
+        let english = Localizer::new("en-US").catalog();
+        let mut used = 0;
+        for source in windows {
+            // Literal ids only; built ids such as `stats-weekday-${day}` are left to review
+            for marker in ["data-i18n=\"", "data-i18n-placeholder=\"", " t('", "(t('"] {
+                for rest in source.split(marker).skip(1) {
+                    let id = &rest[..rest.find(['"', '\'']).unwrap()];
+                    assert!(english.messages.contains_key(id), "{} is not in the catalog", id);
+                    used += 1;
+                }
+            }
+        }
+        assert!(used > 50, "only found {} message ids", used);
+    }
+}
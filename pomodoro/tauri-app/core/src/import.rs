@@ -0,0 +1,256 @@
+use std::fs;
+use std::path::Path;
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use serde::{Serialize, Deserialize};
+use crate::export::{normalize_tag, ExportItem};
+use crate::persistence::{self, History, Phase, SessionRecord};
+
+/// Formats we can read. JSON Lines is our own export format.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "format", rename_all = "lowercase")]
+pub enum ImportFormat {
+    Csv(Box<CsvMapping>),
+    Toggl,
+    Jsonl,
+}
+
+/// Which columns of a generic CSV hold what. Column names match the header row.
+/// A row needs a start (or a date) and either a duration or an end.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CsvMapping {
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub date: Option<String>,
+    pub duration_minutes: Option<String>,
+    pub label: Option<String>,
+    pub tags: Option<String>,       // comma- or space-separated
+    pub completed: Option<String>,  // "false", "0", "no" mean forfeited; anything else completed
+    pub datetime_format: Option<String>,  // chrono format for start/end, default "%Y-%m-%d %H:%M:%S"
+    pub date_format: Option<String>,      // default "%Y-%m-%d"
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ImportSummary {
+    pub days_added: usize,
+    pub days_skipped: usize,  // already in history
+    pub rows_skipped: usize,  // unreadable rows
+}
+
+const DEFAULT_DATETIME: &str = "%Y-%m-%d %H:%M:%S";
+const DEFAULT_DATE: &str = "%Y-%m-%d";
+
+fn local_timestamp(datetime: NaiveDateTime) -> Option<u64> {
+    Local.from_local_datetime(&datetime).earliest().map(|t| t.timestamp() as u64)
+}
+
+fn split_tags(tags: &str) -> Vec<String> {
+    tags.split(|c: char| c == ',' || c.is_whitespace())
+        .map(normalize_tag)
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+fn session(ended_at: u64, minutes: u32, completed: bool, label: Option<String>, tags: Vec<String>) -> Option<SessionRecord> {
+    if minutes == 0 {
+        return None;
+    }
+    let date = Local
+        .timestamp_opt(ended_at as i64, 0)
+        .single()?
+        .format("%Y-%m-%d")
+        .to_string();
+    Some(SessionRecord { date, ended_at, minutes, completed, phase: Phase::Focus, label, tags, reason: None })
+}
+
+/// `HH:MM:SS` (Toggl) or plain minutes.
+fn parse_duration_minutes(value: &str) -> Option<u32> {
+    let parts: Vec<&str> = value.trim().split(':').collect();
+    match parts.as_slice() {
+        [h, m, s] => {
+            let secs = h.parse::<u32>().ok()? * 3600 + m.parse::<u32>().ok()? * 60 + s.parse::<u32>().ok()?;
+            Some((secs + 30) / 60)
+        }
+        [m] => m.parse::<f64>().ok().map(|m| m.round() as u32),
+        _ => None,
+    }
+}
+
+fn read_csv(text: &str, mut row_to_record: impl FnMut(&csv::StringRecord, &csv::StringRecord) -> Option<SessionRecord>) -> (Vec<SessionRecord>, usize) {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(text.as_bytes());
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(_) => return (Vec::new(), 0),
+    };
+    let mut records = Vec::new();
+    let mut skipped = 0;
+    for row in reader.records() {
+        match row.ok().and_then(|row| row_to_record(&headers, &row)) {
+            Some(record) => records.push(record),
+            None => skipped += 1,
+        }
+    }
+    (records, skipped)
+}
+
+fn column<'r>(headers: &csv::StringRecord, row: &'r csv::StringRecord, name: &str) -> Option<&'r str> {
+    let index = headers.iter().position(|h| h.trim().eq_ignore_ascii_case(name))?;
+    row.get(index)
+}
+
+pub fn parse_csv(text: &str, mapping: &CsvMapping) -> (Vec<SessionRecord>, usize) {
+    let datetime_format = mapping.datetime_format.as_deref().unwrap_or(DEFAULT_DATETIME);
+    let date_format = mapping.date_format.as_deref().unwrap_or(DEFAULT_DATE);
+    read_csv(text, |headers, row| {
+        let get = |name: &Option<String>| name.as_deref().and_then(|n| column(headers, row, n));
+        let datetime = |value: &str| NaiveDateTime::parse_from_str(value.trim(), datetime_format).ok();
+
+        let start = get(&mapping.start).and_then(datetime).or_else(|| {
+            get(&mapping.date)
+                .and_then(|d| NaiveDate::parse_from_str(d.trim(), date_format).ok())
+                .map(|d| d.and_time(NaiveTime::MIN))
+        })?;
+        let minutes = match get(&mapping.duration_minutes).and_then(parse_duration_minutes) {
+            Some(minutes) => minutes,
+            None => {
+                let end = get(&mapping.end).and_then(datetime)?;
+                (end - start).num_minutes().max(0) as u32
+            }
+        };
+        let completed = get(&mapping.completed)
+            .map(|v| !matches!(v.trim().to_lowercase().as_str(), "false" | "0" | "no"))
+            .unwrap_or(true);
+        let ended_at = local_timestamp(start)? + minutes as u64 * 60;
+        session(
+            ended_at,
+            minutes,
+            completed,
+            get(&mapping.label).and_then(non_empty),
+            get(&mapping.tags).map(split_tags).unwrap_or_default(),
+        )
+    })
+}
+
+/// Toggl Track "Detailed" time-entry export.
+pub fn parse_toggl(text: &str) -> (Vec<SessionRecord>, usize) {
+    read_csv(text, |headers, row| {
+        let get = |name: &str| column(headers, row, name);
+        let start = NaiveDateTime::parse_from_str(
+            &format!("{} {}", get("Start date")?.trim(), get("Start time")?.trim()),
+            DEFAULT_DATETIME,
+        ).ok()?;
+        let minutes = parse_duration_minutes(get("Duration")?)?;
+        let label = get("Description")
+            .and_then(non_empty)
+            .or_else(|| get("Project").and_then(non_empty));
+        let ended_at = local_timestamp(start)? + minutes as u64 * 60;
+        session(ended_at, minutes, true, label, get("Tags").map(split_tags).unwrap_or_default())
+    })
+}
+
+/// Our own JSON Lines export: sessions, plus whole days for pre-session history.
+pub fn parse_jsonl(text: &str) -> (History, usize) {
+    let mut history = History::default();
+    let mut skipped = 0;
+    for line in text.lines().filter(|l| !l.trim().is_empty()) {
+        match serde_json::from_str::<ExportItem>(line) {
+            Ok(ExportItem::Session(record)) => history.apply(&record),
+            Ok(ExportItem::Day(day)) => {
+                if !history.sessions.iter().any(|s| s.date == day.date) {
+                    history.sessions.push(day);
+                }
+            }
+            Err(_) => skipped += 1,
+        }
+    }
+    (history, skipped)
+}
+
+pub fn parse(text: &str, format: &ImportFormat) -> (History, usize) {
+    let (mut records, skipped) = match format {
+        ImportFormat::Csv(mapping) => parse_csv(text, mapping),
+        ImportFormat::Toggl => parse_toggl(text),
+        ImportFormat::Jsonl => return parse_jsonl(text),
+    };
+    let mut history = History::default();
+    records.sort_by_key(|r| r.ended_at);
+    for record in &records {
+        history.apply(record);
+    }
+    (history, skipped)
+}
+
+/// Read `path` and merge it into the stored history. Days that already have
+/// history are skipped entirely, so re-importing the same file is harmless.
+pub fn import_file(path: &Path, format: &ImportFormat) -> Result<ImportSummary, String> {
+    // A locked store would drop every day and report them all as already there
+    if let Some(error) = persistence::store_error() {
+        return Err(format!("History is unavailable: {}", error));
+    }
+    let text = fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    let (history, rows_skipped) = parse(&text, format);
+    let days = history.sessions.len();
+    let days_added = persistence::store().merge_history(history);
+    Ok(ImportSummary {
+        days_added,
+        days_skipped: days - days_added,
+        rows_skipped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generic_csv_with_mapping() {
+        let text = "When,Length,What,Done\n\
+                    2026-01-05 09:00:00,25,Report,yes\n\
+                    2026-01-05 10:00:00,50,Report,no\n\
+                    not a date,25,,\n";
+        let mapping = CsvMapping {
+            start: Some("when".to_string()),
+            duration_minutes: Some("Length".to_string()),
+            label: Some("What".to_string()),
+            completed: Some("Done".to_string()),
+            ..Default::default()
+        };
+        let (history, skipped) = parse(text, &ImportFormat::Csv(Box::new(mapping)));
+        assert_eq!(skipped, 1);
+        assert_eq!(history.sessions.len(), 1);
+        assert_eq!(history.sessions[0].date, "2026-01-05");
+        assert_eq!(history.sessions[0].completed, 1);
+        assert_eq!(history.sessions[0].forfeited, 1);
+        assert_eq!(history.sessions[0].total_focus_minutes, 25);
+        assert_eq!(history.records[0].label.as_deref(), Some("Report"));
+    }
+
+    #[test]
+    fn test_toggl_csv() {
+        let text = "User,Email,Client,Project,Task,Description,Billable,Start date,Start time,End date,End time,Duration,Tags\n\
+                    Asha,a@x.in,,Thesis,,Chapter 2,No,2026-01-06,08:30:00,2026-01-06,09:15:30,00:45:30,\"Deep, Writing\"\n\
+                    Asha,a@x.in,,Thesis,,,No,2026-01-07,08:30:00,2026-01-07,08:55:00,00:25:00,\n";
+        let (history, skipped) = parse(text, &ImportFormat::Toggl);
+        assert_eq!(skipped, 0);
+        assert_eq!(history.sessions.len(), 2);
+        assert_eq!(history.records[0].minutes, 46);
+        assert_eq!(history.records[0].tags, vec!["deep".to_string(), "writing".to_string()]);
+        assert_eq!(history.records[1].label.as_deref(), Some("Thesis"));
+    }
+
+    #[test]
+    fn test_jsonl_matches_export() {
+        let text = "{\"kind\":\"day\",\"date\":\"2025-11-01\",\"completed\":2,\"forfeited\":0,\"total_focus_minutes\":50}\n\
+                    {\"kind\":\"session\",\"date\":\"2026-01-08\",\"ended_at\":1767862800,\"minutes\":25,\"completed\":true}\n\
+                    garbage\n";
+        let (history, skipped) = parse(text, &ImportFormat::Jsonl);
+        assert_eq!(skipped, 1);
+        assert_eq!(history.sessions.len(), 2);
+        assert_eq!(history.records.len(), 1);
+    }
+}
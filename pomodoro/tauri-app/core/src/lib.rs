@@ -0,0 +1,24 @@
+//! Timer, history, settings and storage for Pomodoro, with no Tauri dependency.
+//! The app in `src-tauri` puts the tray, windows and webview commands on top.
+
+pub mod timer;
+pub mod action;
+pub mod autostart;
+pub mod control;
+pub mod controller;
+pub mod persistence;
+pub mod events;
+pub mod export;
+pub mod history;
+pub mod i18n;
+pub mod import;
+pub mod persister;
+pub mod scheduler;
+pub mod settings;
+pub mod shared;
+pub mod store;
+pub mod tray;
+pub mod watcher;
+
+#[cfg(test)]
+mod testing;
@@ -0,0 +1,254 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::timer::TimerState;
+use crate::persister::StatePersister;
+use crate::settings::{self, EncryptionKey};
+use crate::store::{self, Cipher, Store};
+use serde::{Serialize, Deserialize};
+
+pub fn get_pomodoro_dir() -> PathBuf {
+    let mut path = home::home_dir().expect("Could not find home directory");
+    path.push(".pomodoro");
+    if !path.exists() {
+        fs::create_dir_all(&path).expect("Could not create .pomodoro directory");
+    }
+    path
+}
+
+static STORE: OnceLock<Box<dyn Store>> = OnceLock::new();
+static STORE_ERROR: OnceLock<String> = OnceLock::new();
+
+/// The storage backend chosen in `settings.json`, opened on first use. If history can't be
+/// opened, or `settings.json` doesn't say which backend to use, the timer still runs without
+/// it; see `store_error`.
+pub fn store() -> &'static dyn Store {
+    STORE
+        .get_or_init(|| {
+            let settings = settings::read_settings().map_err(|e| format!("settings.json is invalid: {}", e));
+            settings.and_then(|settings| store::open(&settings, get_pomodoro_dir())).unwrap_or_else(|e| {
+                eprintln!("History unavailable: {}", e);
+                let _ = STORE_ERROR.set(e.clone());
+                store::locked(get_pomodoro_dir(), e)
+            })
+        })
+        .as_ref()
+}
+
+/// Why history is unavailable until the next launch, if it is.
+pub fn store_error() -> Option<&'static str> {
+    store();
+    STORE_ERROR.get().map(String::as_str)
+}
+
+/// How often a running timer's per-second state reaches disk.
+/// `start_time` makes the file authoritative regardless, so this only bounds staleness of `remaining`.
+const STATE_CHECKPOINT: Duration = Duration::from_secs(30);
+
+static PERSISTER: OnceLock<StatePersister<'static>> = OnceLock::new();
+
+fn persister() -> &'static StatePersister<'static> {
+    PERSISTER.get_or_init(|| StatePersister::new(store(), STATE_CHECKPOINT))
+}
+
+/// Write state now. Use on transitions (start, pause, resume, finish, forfeit).
+pub fn save_state(state: &TimerState) {
+    persister().save_now(state);
+}
+
+/// Write state at the next checkpoint. Use for per-second ticks.
+pub fn save_state_later(state: &TimerState) {
+    persister().save_later(state);
+}
+
+/// Write any checkpointed state still pending. Call before exiting.
+pub fn flush_state() {
+    persister().flush();
+}
+
+/// Re-read `state.json` after a change on disk.
+/// Returns the new state if another process wrote it, `None` if it was our own write.
+/// Durations come from settings, not the file, so `current`'s config is carried over.
+pub fn reload_external_state(current: &TimerState) -> Option<TimerState> {
+    let mut state = store().load_state();
+    state.config = current.config.clone();
+    persister().adopt_if_changed(&state).then_some(state)
+}
+
+pub fn load_state() -> TimerState {
+    store().load_state()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HistoryEntry {
+    pub date: String,
+    pub completed: u32,
+    pub forfeited: u32,
+    pub total_focus_minutes: u32,
+}
+
+/// What a session record covers. Breaks never count toward the daily focus totals.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    #[default]
+    Focus,
+    /// A break (or calm session) that ran to the end.
+    Break,
+    /// A break ended early from the Sanctuary or the tray.
+    SkippedBreak,
+}
+
+impl Phase {
+    pub fn is_focus(&self) -> bool {
+        *self == Phase::Focus
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Phase::Focus => "focus",
+            Phase::Break => "break",
+            Phase::SkippedBreak => "skipped_break",
+        }
+    }
+
+    /// Unknown names are read as focus, like records written before phases existed.
+    pub fn parse(name: &str) -> Self {
+        match name {
+            "break" => Phase::Break,
+            "skipped_break" => Phase::SkippedBreak,
+            _ => Phase::Focus,
+        }
+    }
+}
+
+/// A single focus session (finished or forfeited), or a break.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SessionRecord {
+    pub date: String,
+    pub ended_at: u64,  // Unix timestamp
+    pub minutes: u32,
+    pub completed: bool,
+    #[serde(default, skip_serializing_if = "Phase::is_focus")]
+    pub phase: Phase,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Why a focus session was forfeited, if the user said.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct History {
+    /// Daily aggregates, oldest first.
+    pub sessions: Vec<HistoryEntry>,
+    /// Per-session records, oldest first. Absent in files written before the storage rework.
+    #[serde(default)]
+    pub records: Vec<SessionRecord>,
+}
+
+impl SessionRecord {
+    /// What this record adds to its day: `(completed, forfeited, focus minutes)`.
+    pub fn day_totals(&self) -> (u32, u32, u32) {
+        match (self.phase, self.completed) {
+            (Phase::Focus, true) => (1, 0, self.minutes),
+            (Phase::Focus, false) => (0, 1, 0),
+            _ => (0, 0, 0),
+        }
+    }
+}
+
+impl History {
+    /// Fold a session into its day's aggregate and append the record.
+    pub fn apply(&mut self, record: &SessionRecord) {
+        let (completed, forfeited, minutes) = record.day_totals();
+        if let Some(entry) = self.sessions.iter_mut().find(|e| e.date == record.date) {
+            entry.completed += completed;
+            entry.forfeited += forfeited;
+            entry.total_focus_minutes += minutes;
+        } else {
+            self.sessions.push(HistoryEntry {
+                date: record.date.clone(),
+                completed,
+                forfeited,
+                total_focus_minutes: minutes,
+            });
+        }
+        self.records.push(record.clone());
+    }
+
+    /// Drop every day (and its records) dated before `cutoff`.
+    pub fn retain_since(&mut self, cutoff: &str) {
+        self.sessions.retain(|e| e.date.as_str() >= cutoff);
+        self.records.retain(|r| r.date.as_str() >= cutoff);
+    }
+
+    /// Drop per-session records dated before `cutoff`; their daily summaries stay.
+    pub fn compact_before(&mut self, cutoff: &str) {
+        self.records.retain(|r| r.date.as_str() >= cutoff);
+    }
+
+    /// Take the days from `other` that this history doesn't have yet, with their records.
+    pub fn merge_missing(&mut self, other: History) {
+        let History { sessions, records } = other;
+        let new_days: Vec<HistoryEntry> = sessions
+            .into_iter()
+            .filter(|e| !self.sessions.iter().any(|s| s.date == e.date))
+            .collect();
+        self.records.extend(records.into_iter().filter(|r| new_days.iter().any(|d| d.date == r.date)));
+        self.sessions.extend(new_days);
+        self.sessions.sort_by(|a, b| a.date.cmp(&b.date));
+        self.records.sort_by_key(|r| r.ended_at);
+    }
+}
+
+pub fn load_history() -> History {
+    store().load_history()
+}
+
+/// Archive and trim history according to the retention policy in `settings.json`.
+pub fn apply_retention() {
+    let policy = settings::load_settings().retention;
+    store().apply_retention(&policy, chrono::Local::now().date_naive());
+}
+
+/// Encrypt history in place with `key` and remember the key source in settings.
+/// Settings are saved first: a half-finished run leaves plaintext files, which still read fine.
+pub fn encrypt_history(key: EncryptionKey) -> Result<(), String> {
+    let mut settings = settings::read_settings().map_err(|e| format!("settings.json is invalid: {}", e))?;
+    if settings.storage != settings::StorageBackend::Json {
+        return Err("History encryption is only supported by the JSON backend".to_string());
+    }
+    let cipher = Cipher::load(&key, &get_pomodoro_dir())?;
+    settings.encryption = Some(key);
+    settings::save_settings(&settings)?;
+    store().set_encryption(Some(cipher))
+}
+
+/// Decrypt history in place and turn encryption off.
+pub fn decrypt_history() -> Result<(), String> {
+    let mut settings = settings::read_settings().map_err(|e| format!("settings.json is invalid: {}", e))?;
+    store().set_encryption(None)?;
+    settings.encryption = None;
+    settings::save_settings(&settings)
+}
+
+/// A record for a session ending now, for `HistoryService::log`.
+pub fn session_record(phase: Phase, completed: bool, minutes: u32, label: Option<String>, tags: Vec<String>) -> SessionRecord {
+    SessionRecord {
+        date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+        ended_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+        minutes,
+        completed,
+        phase,
+        label,
+        tags,
+        reason: None,
+    }
+}
@@ -0,0 +1,211 @@
+use std::sync::MutexGuard;
+use std::time::{Duration, Instant};
+use crate::shared::Shared;
+use crate::store::Store;
+use crate::timer::TimerState;
+
+/// Write-behind cache for `TimerState`.
+///
+/// Transitions (start, pause, resume, finish, forfeit) go straight to disk via `save_now`.
+/// Ticks go through `save_later`, which only remembers the latest state and writes it
+/// once per checkpoint interval. `flush` writes whatever is pending, and is called on
+/// quit and on shutdown signals.
+pub struct StatePersister<'a> {
+    store: &'a dyn Store,
+    checkpoint: Duration,
+    pending: Shared<Pending>,
+}
+
+struct Pending {
+    state: Option<TimerState>,
+    written: Option<TimerState>,
+    last_write: Instant,
+}
+
+impl<'a> StatePersister<'a> {
+    pub fn new(store: &'a dyn Store, checkpoint: Duration) -> Self {
+        Self {
+            store,
+            checkpoint,
+            pending: Shared::new(Pending {
+                state: None,
+                written: None,
+                last_write: Instant::now(),
+            }),
+        }
+    }
+
+    /// Persist immediately. Use for state transitions.
+    pub fn save_now(&self, state: &TimerState) {
+        let mut pending = self.lock();
+        pending.state = None;
+        self.write(&mut pending, state.clone());
+    }
+
+    /// Persist eventually. Use for per-second updates.
+    pub fn save_later(&self, state: &TimerState) {
+        let mut pending = self.lock();
+        if pending.written.as_ref() == Some(state) {
+            pending.state = None;
+            return;
+        }
+        if pending.last_write.elapsed() >= self.checkpoint {
+            pending.state = None;
+            self.write(&mut pending, state.clone());
+        } else {
+            pending.state = Some(state.clone());
+        }
+    }
+
+    /// Write the pending state, if any.
+    pub fn flush(&self) {
+        let mut pending = self.lock();
+        if let Some(state) = pending.state.take() {
+            self.write(&mut pending, state);
+        }
+    }
+
+    /// Take `state` as what's on disk, if it isn't what we last wrote.
+    /// Returns false for our own writes echoing back through a file watcher.
+    /// Pending ticks are dropped so they can't overwrite the newer external state.
+    pub fn adopt_if_changed(&self, state: &TimerState) -> bool {
+        let mut pending = self.lock();
+        if pending.written.as_ref() == Some(state) {
+            return false;
+        }
+        pending.state = None;
+        pending.written = Some(state.clone());
+        true
+    }
+
+    // A store write that panicked (disk full, permissions) must not stop every later save.
+    // Forget what was written so the next save goes to disk.
+    fn lock(&self) -> MutexGuard<'_, Pending> {
+        self.pending.lock(|poisoned| {
+            eprintln!("State write failed with a panic; retrying on the next save");
+            Pending { state: poisoned.state.clone(), written: None, last_write: poisoned.last_write }
+        })
+    }
+
+    fn write(&self, pending: &mut Pending, state: TimerState) {
+        self.store.save_state(&state);
+        pending.written = Some(state);
+        pending.last_write = Instant::now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use chrono::NaiveDate;
+    use crate::persistence::{History, HistoryEntry, SessionRecord};
+    use crate::settings::RetentionPolicy;
+    use crate::store::Cipher;
+    use crate::timer::Status;
+
+    #[derive(Default)]
+    struct CountingStore {
+        writes: AtomicUsize,
+        /// Panic on the next write, like a persistence `expect` would
+        fail_next: AtomicBool,
+    }
+
+    impl CountingStore {
+        fn writes(&self) -> usize {
+            self.writes.load(Ordering::SeqCst)
+        }
+    }
+
+    impl Store for CountingStore {
+        fn load_state(&self) -> TimerState { TimerState::default() }
+        fn save_state(&self, _state: &TimerState) {
+            if self.fail_next.swap(false, Ordering::SeqCst) {
+                panic!("Could not write state");
+            }
+            self.writes.fetch_add(1, Ordering::SeqCst);
+        }
+        fn load_history(&self) -> History { History::default() }
+        fn history_between(&self, _from: &str, _to: &str) -> Vec<HistoryEntry> { Vec::new() }
+        fn log_session(&self, _record: &SessionRecord) {}
+        fn merge_history(&self, _history: History) -> usize { 0 }
+        fn apply_retention(&self, _policy: &RetentionPolicy, _today: NaiveDate) {}
+        fn set_encryption(&self, _cipher: Option<Cipher>) -> Result<(), String> { Ok(()) }
+    }
+
+    fn ticking(remaining: u64) -> TimerState {
+        TimerState {
+            status: Status::Focus,
+            remaining,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_ticks_do_not_write_before_checkpoint() {
+        let store = CountingStore::default();
+        let persister = StatePersister::new(&store, Duration::from_secs(60));
+
+        persister.save_now(&ticking(1500));
+        for remaining in (0..1500).rev() {
+            persister.save_later(&ticking(remaining));
+        }
+        assert_eq!(store.writes(), 1);
+
+        persister.flush();
+        assert_eq!(store.writes(), 2);
+        persister.flush();
+        assert_eq!(store.writes(), 2);
+    }
+
+    #[test]
+    fn test_checkpoint_writes_pending_ticks() {
+        let store = CountingStore::default();
+        let persister = StatePersister::new(&store, Duration::ZERO);
+
+        persister.save_later(&ticking(1500));
+        persister.save_later(&ticking(1499));
+        assert_eq!(store.writes(), 2);
+    }
+
+    #[test]
+    fn test_adopt_ignores_own_writes_and_drops_pending() {
+        let store = CountingStore::default();
+        let persister = StatePersister::new(&store, Duration::from_secs(60));
+
+        persister.save_now(&ticking(1500));
+        assert!(!persister.adopt_if_changed(&ticking(1500)));
+
+        persister.save_later(&ticking(1400));
+        assert!(persister.adopt_if_changed(&TimerState::default()));
+        persister.flush();
+        assert_eq!(store.writes(), 1);
+    }
+
+    #[test]
+    fn test_unchanged_state_is_not_rewritten() {
+        let store = CountingStore::default();
+        let persister = StatePersister::new(&store, Duration::ZERO);
+
+        persister.save_now(&ticking(1500));
+        persister.save_later(&ticking(1500));
+        persister.flush();
+        assert_eq!(store.writes(), 1);
+    }
+
+    #[test]
+    fn test_keeps_saving_after_a_panicking_write() {
+        let store = CountingStore::default();
+        let persister = StatePersister::new(&store, Duration::from_secs(60));
+
+        persister.save_now(&ticking(1500));
+        store.fail_next.store(true, Ordering::SeqCst);
+        let write = std::panic::AssertUnwindSafe(|| persister.save_now(&ticking(1400)));
+        assert!(std::panic::catch_unwind(write).is_err());
+
+        // The failed state is written again rather than mistaken for what is on disk
+        persister.save_now(&ticking(1400));
+        persister.save_later(&ticking(1400));
+        assert_eq!(store.writes(), 2);
+    }
+}
@@ -0,0 +1,214 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+use chrono::{DateTime, Local, TimeZone};
+use crate::timer::{Status, TimerState};
+
+/// Longest single wait. Waits run on the monotonic clock, so this bounds how late we notice
+/// the wall clock moving under us (sleep, time zone or manual changes).
+pub const MAX_WAIT: Duration = Duration::from_secs(60);
+
+/// When the timer next needs attention after `now`. While a session runs that is the first
+/// whole second at which `shown` (what the tray and windows render from the state) changes,
+/// the next micro-break, or the end of the session, whichever comes first; so a title that
+/// only shows minutes wakes once a minute. Paused and idle timers only need the next local
+/// midnight, for the day rollover.
+pub fn next_deadline<T: PartialEq>(
+    state: &TimerState,
+    now: DateTime<Local>,
+    shown: impl Fn(&TimerState) -> T,
+) -> DateTime<Local> {
+    let midnight = (now.date_naive() + chrono::Duration::days(1)).and_hms_opt(0, 0, 0).unwrap();
+    let midnight = Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .unwrap_or(now + chrono::Duration::hours(1));
+    let running = matches!(state.status, Status::Focus | Status::Break);
+    let (Some(start), true) = (state.start_time, running) else { return midnight };
+    let at = |secs: i64| Local.timestamp_opt(secs, 0).unwrap();
+    let (start, now_secs) = (start as i64, now.timestamp());
+    if now_secs < start {
+        // Clock went back; the tick sorts it out
+        return at(now_secs + 1).min(midnight);
+    }
+
+    let end = start + state.total as i64;
+    let mut next = end.min(now_secs + MAX_WAIT.as_secs() as i64);
+    let interval = state.config.micro_break_interval as i64;
+    if interval > 0 && state.status == Status::Focus {
+        next = next.min(start + ((now_secs - start) / interval + 1) * interval);
+    }
+    // `remaining` counts down by one each second from the start
+    let mut probe = state.clone();
+    probe.remaining = (end - now_secs).max(0) as u64;
+    let current = shown(&probe);
+    for secs in now_secs + 1..next {
+        probe.remaining = (end - secs) as u64;
+        if shown(&probe) != current {
+            next = secs;
+            break;
+        }
+    }
+    at(next).min(midnight)
+}
+
+#[derive(Default)]
+struct Signal {
+    woken: bool,
+    stopped: bool,
+}
+
+/// Interrupts the scheduler's wait, e.g. because a transition moved the next deadline.
+#[derive(Clone, Default)]
+pub struct Waker(Arc<(Mutex<Signal>, Condvar)>);
+
+impl Waker {
+    pub fn wake(&self) {
+        self.0 .0.lock().unwrap().woken = true;
+        self.0 .1.notify_one();
+    }
+
+    fn stop(&self) {
+        self.0 .0.lock().unwrap().stopped = true;
+        self.0 .1.notify_one();
+    }
+
+    /// Wait until `deadline`, a wake-up or shutdown. Returns false on shutdown.
+    fn wait_until(&self, deadline: DateTime<Local>) -> bool {
+        let (signal, condvar) = &*self.0;
+        let mut signal = signal.lock().unwrap();
+        loop {
+            if signal.stopped {
+                return false;
+            }
+            if signal.woken {
+                signal.woken = false;
+                return true;
+            }
+            let wait = (deadline - Local::now()).to_std().unwrap_or_default();
+            if wait.is_zero() {
+                return true;
+            }
+            let (guard, result) = condvar.wait_timeout(signal, wait.min(MAX_WAIT)).unwrap();
+            signal = guard;
+            // Re-plan after a capped wait in case the wall clock jumped
+            if result.timed_out() && wait > MAX_WAIT {
+                return true;
+            }
+        }
+    }
+}
+
+/// Runs `run` on its own thread, then sleeps until the deadline it returns or a wake-up,
+/// and repeats until `shutdown`. A run that panics is retried a second later rather than
+/// ending the thread.
+pub struct Scheduler {
+    waker: Waker,
+    stopped: Receiver<()>,
+}
+
+impl Scheduler {
+    pub fn spawn(waker: Waker, mut run: impl FnMut() -> DateTime<Local> + Send + 'static) -> Self {
+        let (done, stopped) = mpsc::channel();
+        std::thread::spawn({
+            let waker = waker.clone();
+            move || {
+                loop {
+                    // The panic message is already on stderr
+                    let deadline = panic::catch_unwind(AssertUnwindSafe(&mut run))
+                        .unwrap_or_else(|_| Local::now() + chrono::Duration::seconds(1));
+                    if !waker.wait_until(deadline) {
+                        break;
+                    }
+                }
+                let _ = done.send(());
+            }
+        });
+        Self { waker, stopped }
+    }
+
+    /// Stop after the current run, if any, and wait up to `timeout` for that run to end.
+    /// The bound matters when the run is itself waiting on the calling thread (e.g. a tray
+    /// update queued on the main thread during exit). Returns whether the thread ended.
+    pub fn shutdown(self, timeout: Duration) -> bool {
+        self.waker.stop();
+        self.stopped.recv_timeout(timeout).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_deadline() {
+        let now = Local.with_ymd_and_hms(2026, 3, 1, 23, 58, 58).unwrap() + chrono::Duration::milliseconds(400);
+        let at = |h, m, s| Local.with_ymd_and_hms(2026, 3, 1, h, m, s).unwrap();
+        let midnight = Local.with_ymd_and_hms(2026, 3, 2, 0, 0, 0).unwrap();
+        let mut state = TimerState::default();
+        state.reset();
+        state.config.micro_break_interval = 0;
+        let seconds = |s: &TimerState| s.remaining;
+        let minutes = |s: &TimerState| s.remaining.div_ceil(60);
+        assert_eq!(next_deadline(&state, now, seconds), midnight);
+
+        // 1500 s session started 100 s ago: 1400 s (23:20) left
+        state.start_focus(1500);
+        state.start_time = Some(now.timestamp() as u64 - 100);
+        assert_eq!(next_deadline(&state, now, seconds), at(23, 58, 59));
+        // 24 minutes (rounded up) shown until 1380 s are left
+        assert_eq!(next_deadline(&state, now, minutes), at(23, 59, 18));
+        // Nothing shown changes: capped at MAX_WAIT, then midnight
+        assert_eq!(next_deadline(&state, now, |_| ()), at(23, 59, 58));
+        assert_eq!(next_deadline(&state, now + chrono::Duration::seconds(61), |_| ()), midnight);
+
+        // Micro-break every 2 minutes: due 120 s after the start
+        state.config.micro_break_interval = 120;
+        assert_eq!(next_deadline(&state, now, |_| ()), at(23, 59, 18));
+        state.config.micro_break_interval = 0;
+
+        // The session ends before anything else
+        state.start_time = Some(now.timestamp() as u64 - 1495);
+        assert_eq!(next_deadline(&state, now, |_| ()), at(23, 59, 3));
+
+        state.toggle();
+        assert_eq!(state.status, Status::Paused);
+        assert_eq!(next_deadline(&state, now, seconds), midnight);
+    }
+
+    #[test]
+    fn test_wake_and_shutdown() {
+        let (runs, ran) = mpsc::channel();
+        let waker = Waker::default();
+        let scheduler = Scheduler::spawn(waker.clone(), move || {
+            let _ = runs.send(());
+            Local::now() + chrono::Duration::hours(1)
+        });
+        let timeout = Duration::from_secs(5);
+        ran.recv_timeout(timeout).unwrap();
+
+        // Sleeping for an hour, but a wake-up runs it again right away
+        waker.wake();
+        ran.recv_timeout(timeout).unwrap();
+        assert!(ran.recv_timeout(Duration::from_millis(100)).is_err());
+
+        assert!(scheduler.shutdown(timeout));
+        assert!(ran.recv_timeout(Duration::from_millis(100)).is_err());
+    }
+
+    #[test]
+    fn test_survives_a_panicking_run() {
+        let (runs, ran) = mpsc::channel();
+        let mut first = true;
+        let scheduler = Scheduler::spawn(Waker::default(), move || {
+            if std::mem::take(&mut first) {
+                panic!("first run");
+            }
+            let _ = runs.send(());
+            Local::now() + chrono::Duration::hours(1)
+        });
+        ran.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(scheduler.shutdown(Duration::from_secs(5)));
+    }
+}
@@ -0,0 +1,318 @@
+use std::fs;
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize};
+use crate::persistence::get_pomodoro_dir;
+use crate::timer::TimerConfig;
+use crate::tray::{self, IconStyle, TitleDigits, TitleFormat};
+
+/// Which backend holds timer state and session history.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    #[default]
+    Json,
+    Sqlite,
+}
+
+/// How the app launches at login on Linux. macOS always uses a LaunchAgent,
+/// Windows the `Run` registry key.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AutostartMethod {
+    /// `~/.config/autostart/*.desktop`, honoured by most desktop environments.
+    #[default]
+    Xdg,
+    /// A systemd user unit bound to `graphical-session.target`.
+    Systemd,
+}
+
+/// Global hotkeys in Tauri accelerator syntax, e.g. `"CmdOrCtrl+Alt+P"`. An empty string turns one off.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Shortcuts {
+    pub toggle: String,
+    pub forfeit: String,
+    pub extend: String,
+    pub stats: String,
+    pub capture: String,
+}
+
+impl Default for Shortcuts {
+    fn default() -> Self {
+        Self {
+            toggle: "CmdOrCtrl+Alt+P".to_string(),
+            forfeit: "CmdOrCtrl+Alt+F".to_string(),
+            extend: "CmdOrCtrl+Alt+E".to_string(),
+            stats: "CmdOrCtrl+Alt+S".to_string(),
+            capture: "CmdOrCtrl+Alt+N".to_string(),
+        }
+    }
+}
+
+impl Shortcuts {
+    /// `(action, accelerator)` for every enabled shortcut. Actions are tray menu ids.
+    pub fn bindings(&self) -> Vec<(&'static str, &str)> {
+        [
+            ("toggle", &self.toggle),
+            ("forfeit", &self.forfeit),
+            ("extend", &self.extend),
+            ("stats", &self.stats),
+            ("capture", &self.capture),
+        ]
+        .into_iter()
+        .map(|(action, accelerator)| (action, accelerator.trim()))
+        .filter(|(_, accelerator)| !accelerator.is_empty())
+        .collect()
+    }
+}
+
+/// How long history is kept and in what detail.
+/// The defaults keep everything; nothing is ever deleted unless asked for.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    /// Delete days older than this. `None` keeps history forever.
+    pub max_age_days: Option<u32>,
+    /// Drop per-session records older than this, keeping only their daily summaries.
+    pub compact_after_days: Option<u32>,
+    /// JSON backend only: move previous years out of `history.json` into `archive/history-YYYY.json`.
+    pub archive_by_year: bool,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            max_age_days: None,
+            compact_after_days: None,
+            archive_by_year: true,
+        }
+    }
+}
+
+/// Where the history encryption key comes from.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "source", rename_all = "lowercase")]
+pub enum EncryptionKey {
+    /// Passphrase read from an environment variable at launch.
+    Passphrase {
+        #[serde(default = "default_passphrase_env")]
+        env: String,
+    },
+    /// Any file whose contents act as the secret.
+    Keyfile { path: PathBuf },
+}
+
+fn default_passphrase_env() -> String {
+    "POMODORO_PASSPHRASE".to_string()
+}
+
+/// User configuration, read from `~/.pomodoro/settings.json`.
+/// Missing fields fall back to their defaults so older files keep working.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub focus_minutes: u32,
+    pub short_break_minutes: u32,
+    pub long_break_minutes: u32,
+    /// Focus sessions per long break; 0 never takes a long break.
+    pub long_break_every: u32,
+    pub micro_breaks: bool,
+    pub micro_break_interval_minutes: u32,
+    pub calm_minutes: u32,
+    /// Tray title, e.g. `"{icon} {time}"`. Placeholders: {icon} {time} {minutes} {sessions} {goal}.
+    pub title_template: String,
+    pub title_digits: TitleDigits,
+    /// Focus sessions per day, shown by `{goal}`. 0 for no goal.
+    pub daily_goal: u32,
+    /// Progress ring, pie, or the plain app icon with the title glyph.
+    pub tray_icon: IconStyle,
+    /// Language tag such as `"de"`. `None` follows the system locale.
+    pub locale: Option<String>,
+    pub launch_at_login: bool,
+    pub autostart: AutostartMethod,
+    pub shortcuts: Shortcuts,
+    pub storage: StorageBackend,
+    pub retention: RetentionPolicy,
+    /// Encrypt history at rest (JSON backend). `None` keeps plaintext.
+    pub encryption: Option<EncryptionKey>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            focus_minutes: 25,
+            short_break_minutes: 5,
+            long_break_minutes: 15,
+            long_break_every: 4,
+            micro_breaks: true,
+            micro_break_interval_minutes: 5,
+            calm_minutes: 5,
+            title_template: TitleFormat::default().template,
+            title_digits: TitleDigits::default(),
+            daily_goal: TitleFormat::default().daily_goal,
+            tray_icon: IconStyle::default(),
+            locale: None,
+            launch_at_login: false,
+            autostart: AutostartMethod::default(),
+            shortcuts: Shortcuts::default(),
+            storage: StorageBackend::default(),
+            retention: RetentionPolicy::default(),
+            encryption: None,
+        }
+    }
+}
+
+impl Settings {
+    /// Every problem with these settings, in field order. Empty means valid.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let mut range = |name: &str, value: u32, min: u32, max: u32| {
+            if value < min || value > max {
+                errors.push(format!("{} must be between {} and {} (got {})", name, min, max, value));
+            }
+        };
+        range("focus_minutes", self.focus_minutes, 1, 180);
+        range("short_break_minutes", self.short_break_minutes, 1, 60);
+        range("long_break_minutes", self.long_break_minutes, 1, 120);
+        range("long_break_every", self.long_break_every, 0, 12);
+        range("micro_break_interval_minutes", self.micro_break_interval_minutes, 1, 60);
+        range("calm_minutes", self.calm_minutes, 1, 180);
+        range("daily_goal", self.daily_goal, 0, 24);
+        for name in tray::unknown_placeholders(&self.title_template) {
+            errors.push(format!("title_template has unknown placeholder {{{}}}", name));
+        }
+        let bindings = self.shortcuts.bindings();
+        for (i, (action, accelerator)) in bindings.iter().enumerate() {
+            let key = accelerator.to_lowercase().replace(' ', "");
+            if let Some((other, _)) = bindings[..i].iter().find(|(_, a)| a.to_lowercase().replace(' ', "") == key) {
+                errors.push(format!("shortcuts.{} uses {} which is already bound to shortcuts.{}", action, accelerator, other));
+            }
+        }
+        if self.retention.max_age_days == Some(0) {
+            errors.push("retention.max_age_days must be at least 1".to_string());
+        }
+        if self.retention.compact_after_days == Some(0) {
+            errors.push("retention.compact_after_days must be at least 1".to_string());
+        }
+        errors
+    }
+
+    pub fn title_format(&self) -> TitleFormat {
+        TitleFormat {
+            template: self.title_template.clone(),
+            digits: self.title_digits,
+            daily_goal: self.daily_goal,
+        }
+    }
+
+    pub fn timer_config(&self) -> TimerConfig {
+        TimerConfig {
+            focus: self.focus_minutes as u64 * 60,
+            short_break: self.short_break_minutes as u64 * 60,
+            long_break: self.long_break_minutes as u64 * 60,
+            long_break_every: self.long_break_every,
+            micro_break_interval: if self.micro_breaks {
+                self.micro_break_interval_minutes as u64 * 60
+            } else {
+                0
+            },
+            calm: self.calm_minutes as u64 * 60,
+        }
+    }
+}
+
+pub fn settings_path() -> PathBuf {
+    let mut path = get_pomodoro_dir();
+    path.push("settings.json");
+    path
+}
+
+pub fn load_settings() -> Settings {
+    match fs::read_to_string(settings_path()) {
+        Ok(json) => serde_json::from_str(&json).unwrap_or_default(),
+        Err(_) => Settings::default(),
+    }
+}
+
+/// Strict counterpart of `load_settings` for hand-edited files: the error names what is wrong.
+pub fn parse_settings(json: &str) -> Result<Settings, String> {
+    let settings: Settings = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    let errors = settings.validate();
+    if !errors.is_empty() {
+        return Err(errors.join("; "));
+    }
+    Ok(settings)
+}
+
+/// A missing file is fine (defaults); an unreadable, malformed or invalid one is an error.
+pub fn read_settings() -> Result<Settings, String> {
+    match fs::read_to_string(settings_path()) {
+        Ok(json) => parse_settings(&json),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Settings::default()),
+        Err(e) => Err(format!("Could not read settings.json: {}", e)),
+    }
+}
+
+pub fn save_settings(settings: &Settings) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(settings_path(), json).map_err(|e| format!("Could not write settings.json: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_file_keeps_defaults() {
+        let settings: Settings = serde_json::from_str(r#"{"focus_minutes": 50, "storage": "sqlite"}"#).unwrap();
+        assert_eq!(settings.focus_minutes, 50);
+        assert_eq!(settings.short_break_minutes, 5);
+        assert_eq!(settings.storage, StorageBackend::Sqlite);
+        assert!(settings.validate().is_empty());
+    }
+
+    #[test]
+    fn test_validate_reports_every_bad_field() {
+        let settings = Settings {
+            focus_minutes: 0,
+            micro_break_interval_minutes: 90,
+            ..Default::default()
+        };
+        let errors = settings.validate();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("focus_minutes"));
+    }
+
+    #[test]
+    fn test_timer_config_disables_micro_breaks() {
+        let settings = Settings { micro_breaks: false, focus_minutes: 50, ..Default::default() };
+        let config = settings.timer_config();
+        assert_eq!(config.focus, 3000);
+        assert_eq!(config.micro_break_interval, 0);
+    }
+
+    #[test]
+    fn test_parse_settings_reports_problems() {
+        assert_eq!(parse_settings("{}").unwrap(), Settings::default());
+
+        let err = parse_settings(r#"{"focus_minutes": 25,}"#).unwrap_err();
+        assert!(err.contains("line 1"), "{}", err);
+
+        let err = parse_settings(r#"{"focus_minutes": "long"}"#).unwrap_err();
+        assert!(err.contains("invalid type"), "{}", err);
+
+        let err = parse_settings(r#"{"focus_minutes": 0, "calm_minutes": 500}"#).unwrap_err();
+        assert!(err.contains("focus_minutes") && err.contains("; calm_minutes"), "{}", err);
+    }
+
+    #[test]
+    fn test_duplicate_shortcuts_are_reported() {
+        let mut settings = Settings::default();
+        settings.shortcuts.extend = "cmdorctrl + alt + p".to_string();
+        settings.shortcuts.stats = String::new();
+        let errors = settings.validate();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("shortcuts.extend"), "{}", errors[0]);
+        assert_eq!(settings.shortcuts.bindings().len(), 4);
+    }
+}
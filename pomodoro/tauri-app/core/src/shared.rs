@@ -0,0 +1,49 @@
+use std::sync::{Mutex, MutexGuard};
+
+/// A mutex that outlives a panic while it was held. The value a panic left behind may be
+/// half-updated, so `lock` swaps in a restored one instead of failing every later caller.
+pub struct Shared<T> {
+    value: Mutex<T>,
+}
+
+impl<T> Shared<T> {
+    pub const fn new(value: T) -> Self {
+        Self { value: Mutex::new(value) }
+    }
+
+    /// Lock; if a panic poisoned the lock, first replace the value with `restore(&poisoned)`.
+    pub fn lock(&self, restore: impl FnOnce(&T) -> T) -> MutexGuard<'_, T> {
+        match self.value.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => {
+                let mut guard = poisoned.into_inner();
+                *guard = restore(&guard);
+                self.value.clear_poison();
+                guard
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restores_after_panic() {
+        let shared = Shared::new(1);
+        let _ = std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    let mut value = shared.lock(|_| unreachable!());
+                    *value = 2;
+                    panic!("while holding the lock");
+                })
+                .join()
+        });
+
+        assert_eq!(*shared.lock(|poisoned| poisoned * 10), 20);
+        // Restored once; the lock is healthy again
+        assert_eq!(*shared.lock(|_| unreachable!()), 20);
+    }
+}
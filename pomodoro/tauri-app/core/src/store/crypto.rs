@@ -0,0 +1,97 @@
+use std::fs;
+use std::path::Path;
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use crate::settings::EncryptionKey;
+
+/// Marks an encrypted file. Plaintext history is JSON and can never start with it.
+const MAGIC: &[u8] = b"POMODORO-ENC1\n";
+const NONCE_LEN: usize = 24;
+
+/// XChaCha20-Poly1305 with a key derived by Argon2id from a passphrase or keyfile.
+///
+/// Encrypted file layout: `MAGIC || nonce (24 bytes) || ciphertext + tag`.
+/// The Argon2 salt is shared by the data directory (`encryption.salt`) so the key is
+/// derived once per launch rather than once per file read.
+pub struct Cipher {
+    aead: XChaCha20Poly1305,
+}
+
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+impl Cipher {
+    pub fn derive(secret: &[u8], salt: &[u8]) -> Result<Self, String> {
+        let mut key = [0u8; 32];
+        Argon2::default()
+            .hash_password_into(secret, salt, &mut key)
+            .map_err(|e| format!("Could not derive key: {}", e))?;
+        Ok(Self { aead: XChaCha20Poly1305::new(&key.into()) })
+    }
+
+    /// Resolve the configured key source. The salt is created on first use.
+    pub fn load(source: &EncryptionKey, dir: &Path) -> Result<Self, String> {
+        let secret = match source {
+            EncryptionKey::Passphrase { env } => std::env::var(env)
+                .map_err(|_| format!("History is encrypted but ${} is not set", env))?
+                .into_bytes(),
+            EncryptionKey::Keyfile { path } => fs::read(path)
+                .map_err(|e| format!("Could not read keyfile {}: {}", path.display(), e))?,
+        };
+        if secret.is_empty() {
+            return Err("Encryption passphrase/keyfile is empty".to_string());
+        }
+
+        let salt_path = dir.join("encryption.salt");
+        let salt = match fs::read(&salt_path) {
+            Ok(salt) => salt,
+            Err(_) => {
+                let mut salt = vec![0u8; 16];
+                OsRng.fill_bytes(&mut salt);
+                fs::write(&salt_path, &salt).map_err(|e| format!("Could not write salt: {}", e))?;
+                salt
+            }
+        };
+        Self::derive(&secret, &salt)
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self.aead.encrypt(&nonce, plaintext).expect("Could not encrypt history");
+        [MAGIC, nonce.as_slice(), &ciphertext].concat()
+    }
+
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        let body = data.strip_prefix(MAGIC).ok_or("Not an encrypted history file")?;
+        if body.len() < NONCE_LEN {
+            return Err("Encrypted history file is truncated".to_string());
+        }
+        let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+        self.aead
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| "Could not decrypt history: wrong key or corrupted file".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_and_wrong_key() {
+        let cipher = Cipher::derive(b"lotus", b"0123456789abcdef").unwrap();
+        let sealed = cipher.encrypt(b"{\"sessions\":[]}");
+        assert!(is_encrypted(&sealed));
+        assert_eq!(cipher.decrypt(&sealed).unwrap(), b"{\"sessions\":[]}");
+
+        let other = Cipher::derive(b"stone", b"0123456789abcdef").unwrap();
+        assert!(other.decrypt(&sealed).is_err());
+
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(cipher.decrypt(&tampered).is_err());
+    }
+}
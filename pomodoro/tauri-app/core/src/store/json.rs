@@ -0,0 +1,386 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use chrono::{Datelike, NaiveDate};
+use crate::persistence::{History, HistoryEntry, SessionRecord};
+use crate::settings::RetentionPolicy;
+use crate::timer::TimerState;
+use super::crypto::{is_encrypted, Cipher};
+use super::lock::{write_atomic, FileLock};
+use super::{cutoff, Store};
+
+/// The original file layout: `state.json` and `history.json` in the data directory.
+///
+/// With yearly archiving on, `history.json` only holds the current year; earlier years
+/// live in `archive/history-YYYY.json` and are read back transparently by the queries.
+///
+/// Every access holds an advisory lock: `state.json.lock` for state, and
+/// `history.json.lock` for `history.json` and the archives together.
+///
+/// With a cipher set, history files are written encrypted. Reads accept either form,
+/// so switching encryption on or off never strands existing files.
+pub struct JsonStore {
+    dir: PathBuf,
+    cipher: RwLock<Option<Cipher>>,
+}
+
+fn year_of(date: &str) -> i32 {
+    date.get(..4).and_then(|y| y.parse().ok()).unwrap_or(0)
+}
+
+impl JsonStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self::with_cipher(dir, None)
+    }
+
+    pub fn with_cipher(dir: PathBuf, cipher: Option<Cipher>) -> Self {
+        Self { dir, cipher: RwLock::new(cipher) }
+    }
+
+    /// Whether every history file can be read: decrypted with the cipher we have, and parsed.
+    /// Reads panic otherwise, so `open` checks first.
+    pub fn check_readable(&self) -> Result<(), String> {
+        let archives = self.archived_years().into_iter().map(|year| self.archive_path(year));
+        for path in std::iter::once(self.history_path()).chain(archives) {
+            self.decode(&path)?;
+        }
+        Ok(())
+    }
+
+    fn decode(&self, path: &Path) -> Result<History, String> {
+        let data = match fs::read(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(History::default()),
+            Err(e) => return Err(format!("Could not read {}: {}", path.display(), e)),
+        };
+        let json = if is_encrypted(&data) {
+            let cipher = self.cipher.read().unwrap();
+            let cipher = cipher.as_ref().ok_or("History is encrypted but no key is configured")?;
+            cipher.decrypt(&data)?
+        } else {
+            data
+        };
+        if json.is_empty() {
+            return Ok(History::default());
+        }
+        // Never read as empty: the next write would replace the file with it
+        serde_json::from_slice(&json).map_err(|e| format!("{} is damaged: {}", path.display(), e))
+    }
+
+    fn read_history(&self, path: &Path) -> History {
+        self.decode(path).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    fn write_history(&self, path: &Path, history: &History) {
+        let json = serde_json::to_string_pretty(history).expect("Could not serialize history");
+        match self.cipher.read().unwrap().as_ref() {
+            Some(cipher) => write_atomic(path, &cipher.encrypt(json.as_bytes())),
+            None => write_atomic(path, json.as_bytes()),
+        }
+    }
+
+    fn state_path(&self) -> PathBuf {
+        self.dir.join("state.json")
+    }
+
+    fn history_path(&self) -> PathBuf {
+        self.dir.join("history.json")
+    }
+
+    fn archive_path(&self, year: i32) -> PathBuf {
+        self.dir.join("archive").join(format!("history-{}.json", year))
+    }
+
+    /// Years with an archive file, oldest first.
+    fn archived_years(&self) -> Vec<i32> {
+        let mut years: Vec<i32> = fs::read_dir(self.dir.join("archive"))
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .filter_map(|e| {
+                        let name = e.file_name().into_string().ok()?;
+                        name.strip_prefix("history-")?.strip_suffix(".json")?.parse().ok()
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        years.sort();
+        years
+    }
+
+    fn live_history(&self) -> History {
+        self.read_history(&self.history_path())
+    }
+
+    /// Move every year before `current_year` out of `history.json`.
+    /// The archive is written before the live file, and days already archived are
+    /// skipped, so an interrupted run simply finishes on the next one.
+    fn archive_past_years(&self, current_year: i32) {
+        let mut live = self.live_history();
+        let mut by_year: BTreeMap<i32, History> = BTreeMap::new();
+        for entry in live.sessions.iter().filter(|e| year_of(&e.date) < current_year) {
+            by_year.entry(year_of(&entry.date)).or_default().sessions.push(entry.clone());
+        }
+        if by_year.is_empty() {
+            return;
+        }
+        for record in live.records.iter().filter(|r| year_of(&r.date) < current_year) {
+            by_year.entry(year_of(&record.date)).or_default().records.push(record.clone());
+        }
+
+        fs::create_dir_all(self.dir.join("archive")).expect("Could not create archive directory");
+        for (year, moved) in by_year {
+            let path = self.archive_path(year);
+            let mut archive = self.read_history(&path);
+            archive.merge_missing(moved);
+            self.write_history(&path, &archive);
+        }
+
+        let first_day = format!("{}-01-01", current_year);
+        live.retain_since(&first_day);
+        self.write_history(&self.history_path(), &live);
+    }
+}
+
+impl Store for JsonStore {
+    fn load_state(&self) -> TimerState {
+        let path = self.state_path();
+        let _lock = FileLock::shared(&path);
+        if path.exists() {
+            let json = fs::read_to_string(path).expect("Could not read state.json");
+            serde_json::from_str(&json).unwrap_or_else(|_| TimerState::default())
+        } else {
+            TimerState::default()
+        }
+    }
+
+    fn save_state(&self, state: &TimerState) {
+        let json = serde_json::to_string_pretty(state).expect("Could not serialize state");
+        let path = self.state_path();
+        let _lock = FileLock::exclusive(&path);
+        write_atomic(&path, json.as_bytes());
+    }
+
+    fn load_history(&self) -> History {
+        let _lock = FileLock::shared(&self.history_path());
+        let mut history = History::default();
+        for year in self.archived_years() {
+            let archive = self.read_history(&self.archive_path(year));
+            history.sessions.extend(archive.sessions);
+            history.records.extend(archive.records);
+        }
+        history.merge_missing(self.live_history());
+        history
+    }
+
+    fn history_between(&self, from: &str, to: &str) -> Vec<HistoryEntry> {
+        let _lock = FileLock::shared(&self.history_path());
+        let (first, last) = (year_of(from), year_of(to));
+        let mut entries: Vec<HistoryEntry> = self
+            .archived_years()
+            .into_iter()
+            .filter(|year| (first..=last).contains(year))
+            .flat_map(|year| self.read_history(&self.archive_path(year)).sessions)
+            .collect();
+        for entry in self.live_history().sessions {
+            if !entries.iter().any(|e| e.date == entry.date) {
+                entries.push(entry);
+            }
+        }
+        entries.retain(|e| e.date.as_str() >= from && e.date.as_str() <= to);
+        entries.sort_by(|a, b| a.date.cmp(&b.date));
+        entries
+    }
+
+    fn log_session(&self, record: &SessionRecord) {
+        let _lock = FileLock::exclusive(&self.history_path());
+        let mut history = self.live_history();
+        history.apply(record);
+        self.write_history(&self.history_path(), &history);
+    }
+
+    fn merge_history(&self, history: History) -> usize {
+        let _lock = FileLock::exclusive(&self.history_path());
+        let mut live = self.live_history();
+        let mut known: Vec<String> = live.sessions.iter().map(|e| e.date.clone()).collect();
+        for year in self.archived_years() {
+            known.extend(self.read_history(&self.archive_path(year)).sessions.into_iter().map(|e| e.date));
+        }
+
+        let History { mut sessions, mut records } = history;
+        sessions.retain(|e| !known.contains(&e.date));
+        records.retain(|r| sessions.iter().any(|e| e.date == r.date));
+        let added = sessions.len();
+        if added > 0 {
+            live.merge_missing(History { sessions, records });
+            self.write_history(&self.history_path(), &live);
+        }
+        added
+    }
+
+    fn set_encryption(&self, cipher: Option<Cipher>) -> Result<(), String> {
+        let _lock = FileLock::exclusive(&self.history_path());
+        let mut paths: Vec<PathBuf> = self.archived_years().into_iter().map(|y| self.archive_path(y)).collect();
+        paths.push(self.history_path());
+        let histories: Vec<(PathBuf, History)> = paths
+            .into_iter()
+            .filter(|p| p.exists())
+            .map(|p| {
+                let history = self.read_history(&p);
+                (p, history)
+            })
+            .collect();
+
+        *self.cipher.write().unwrap() = cipher;
+        for (path, history) in &histories {
+            self.write_history(path, history);
+        }
+        Ok(())
+    }
+
+    fn apply_retention(&self, policy: &RetentionPolicy, today: NaiveDate) {
+        let _lock = FileLock::exclusive(&self.history_path());
+        if policy.archive_by_year {
+            self.archive_past_years(today.year());
+        }
+
+        let expire = policy.max_age_days.map(|days| cutoff(today, days));
+        let compact = policy.compact_after_days.map(|days| cutoff(today, days));
+        if expire.is_none() && compact.is_none() {
+            return;
+        }
+
+        let trim = |history: &mut History| {
+            if let Some(cutoff) = &expire {
+                history.retain_since(cutoff);
+            }
+            if let Some(cutoff) = &compact {
+                history.compact_before(cutoff);
+            }
+        };
+
+        for year in self.archived_years() {
+            let path = self.archive_path(year);
+            let mut archive = self.read_history(&path);
+            trim(&mut archive);
+            if archive.sessions.is_empty() {
+                fs::remove_file(&path).expect("Could not remove expired archive");
+            } else {
+                self.write_history(&path, &archive);
+            }
+        }
+
+        let mut live = self.live_history();
+        trim(&mut live);
+        self.write_history(&self.history_path(), &live);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    fn temp_store(name: &str) -> (TempDir, JsonStore) {
+        let dir = TempDir::new(name);
+        let store = JsonStore::new(dir.to_path_buf());
+        (dir, store)
+    }
+
+    fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_history_is_never_truncated() {
+        let (_dir, store) = temp_store("unbounded");
+        let start = day(2025, 1, 1);
+        for i in 0..60 {
+            let date = (start + chrono::Duration::days(i)).format("%Y-%m-%d").to_string();
+            store.log_session(&SessionRecord::focus(&date, 25));
+        }
+        assert_eq!(store.load_history().sessions.len(), 60);
+    }
+
+    #[test]
+    fn test_archive_by_year_spans_queries() {
+        let (_dir, store) = temp_store("archive");
+        store.log_session(&SessionRecord::focus("2024-12-30", 25));
+        store.log_session(&SessionRecord::focus("2025-12-31", 25));
+        store.log_session(&SessionRecord::focus("2026-01-02", 25));
+
+        store.apply_retention(&RetentionPolicy::default(), day(2026, 1, 2));
+        store.apply_retention(&RetentionPolicy::default(), day(2026, 1, 2));
+
+        assert_eq!(store.live_history().sessions.len(), 1);
+        assert_eq!(store.archived_years(), vec![2024, 2025]);
+        assert_eq!(store.history_between("2025-12-01", "2026-01-31").len(), 2);
+        assert_eq!(store.load_history().sessions.len(), 3);
+        assert_eq!(store.load_history().records.len(), 3);
+    }
+
+    #[test]
+    fn test_merge_history_skips_known_days() {
+        let (_dir, store) = temp_store("merge");
+        store.log_session(&SessionRecord::focus("2024-05-01", 25));
+        store.log_session(&SessionRecord::focus("2026-01-02", 25));
+        store.apply_retention(&RetentionPolicy::default(), day(2026, 1, 2));
+
+        let mut imported = History::default();
+        imported.apply(&SessionRecord::focus("2024-05-01", 50));
+        imported.apply(&SessionRecord::focus("2025-07-01", 50));
+        imported.apply(&SessionRecord::focus("2026-01-02", 50));
+        assert_eq!(store.merge_history(imported.clone()), 1);
+        assert_eq!(store.merge_history(imported), 0);
+
+        let history = store.load_history();
+        assert_eq!(history.sessions.len(), 3);
+        assert_eq!(history.records.len(), 3);
+        assert_eq!(history.sessions[0].total_focus_minutes, 25);
+    }
+
+    #[test]
+    fn test_encrypt_and_decrypt_in_place() {
+        let (_dir, store) = temp_store("encrypt");
+        store.log_session(&SessionRecord::focus("2025-03-01", 25));
+        store.log_session(&SessionRecord::focus("2026-03-01", 25));
+        store.apply_retention(&RetentionPolicy::default(), day(2026, 3, 1));
+
+        let cipher = || Cipher::derive(b"lotus", b"0123456789abcdef").unwrap();
+        store.set_encryption(Some(cipher())).unwrap();
+        assert!(is_encrypted(&fs::read(store.history_path()).unwrap()));
+        assert!(is_encrypted(&fs::read(store.archive_path(2025)).unwrap()));
+        store.log_session(&SessionRecord::focus("2026-03-02", 25));
+        assert_eq!(store.load_history().sessions.len(), 3);
+
+        let reopened = JsonStore::with_cipher(store.dir.clone(), Some(cipher()));
+        assert_eq!(reopened.history_between("2025-01-01", "2026-12-31").len(), 3);
+
+        reopened.set_encryption(None).unwrap();
+        let plain = fs::read_to_string(store.history_path()).unwrap();
+        assert!(plain.contains("2026-03-02"));
+        assert_eq!(JsonStore::new(store.dir.clone()).load_history().sessions.len(), 3);
+    }
+
+    #[test]
+    fn test_compaction_and_expiry() {
+        let (_dir, store) = temp_store("compact");
+        store.log_session(&SessionRecord::focus("2025-06-01", 25));
+        store.log_session(&SessionRecord::focus("2026-01-01", 25));
+        store.log_session(&SessionRecord::focus("2026-03-01", 25));
+
+        let policy = RetentionPolicy {
+            max_age_days: Some(200),
+            compact_after_days: Some(30),
+            archive_by_year: true,
+        };
+        store.apply_retention(&policy, day(2026, 3, 10));
+
+        let history = store.load_history();
+        assert_eq!(history.sessions.len(), 2);
+        assert_eq!(history.records.len(), 1);
+        assert_eq!(history.records[0].date, "2026-03-01");
+        assert_eq!(store.archived_years(), Vec::<i32>::new());
+    }
+}
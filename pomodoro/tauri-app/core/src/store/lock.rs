@@ -0,0 +1,55 @@
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+
+/// Advisory lock on `<file>.lock`, released on drop.
+///
+/// Other tools sharing `~/.pomodoro` can take the same lock, e.g. from a shell script:
+/// `flock ~/.pomodoro/state.json.lock -c '...'`.
+pub struct FileLock {
+    file: File,
+}
+
+fn lock_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".lock");
+    path.with_file_name(name)
+}
+
+fn open_lock(path: &Path) -> File {
+    OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(lock_path(path))
+        .expect("Could not open lock file")
+}
+
+impl FileLock {
+    /// For read-modify-write and plain writes.
+    pub fn exclusive(path: &Path) -> Self {
+        let file = open_lock(path);
+        file.lock().expect("Could not lock file");
+        Self { file }
+    }
+
+    /// For reads.
+    pub fn shared(path: &Path) -> Self {
+        let file = open_lock(path);
+        file.lock_shared().expect("Could not lock file");
+        Self { file }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+    }
+}
+
+/// Replace `path` via a temp file + rename so lock-free readers never see a partial file.
+pub fn write_atomic(path: &Path, contents: &[u8]) {
+    let mut tmp = path.as_os_str().to_os_string();
+    tmp.push(".tmp");
+    fs::write(&tmp, contents).expect("Could not write temp file");
+    fs::rename(&tmp, path).expect("Could not replace file");
+}
@@ -0,0 +1,52 @@
+use chrono::NaiveDate;
+use crate::persistence::{History, HistoryEntry, SessionRecord};
+use crate::settings::RetentionPolicy;
+use crate::timer::TimerState;
+use super::{Cipher, Store};
+
+/// Stands in when history can't be opened, e.g. it is encrypted and the passphrase is not set.
+/// The timer keeps working; history reads come back empty and writes are dropped, so nothing
+/// overwrites the files we could not read.
+pub struct LockedStore {
+    state: Box<dyn Store>,
+    reason: String,
+}
+
+impl LockedStore {
+    /// `state` only ever handles `state.json`.
+    pub fn new(state: Box<dyn Store>, reason: String) -> Self {
+        Self { state, reason }
+    }
+}
+
+impl Store for LockedStore {
+    fn load_state(&self) -> TimerState {
+        self.state.load_state()
+    }
+
+    fn save_state(&self, state: &TimerState) {
+        self.state.save_state(state);
+    }
+
+    fn load_history(&self) -> History {
+        History::default()
+    }
+
+    fn history_between(&self, _from: &str, _to: &str) -> Vec<HistoryEntry> {
+        Vec::new()
+    }
+
+    fn log_session(&self, record: &SessionRecord) {
+        eprintln!("Not recording the session ending at {}: {}", record.ended_at, self.reason);
+    }
+
+    fn merge_history(&self, _history: History) -> usize {
+        0
+    }
+
+    fn apply_retention(&self, _policy: &RetentionPolicy, _today: NaiveDate) {}
+
+    fn set_encryption(&self, _cipher: Option<Cipher>) -> Result<(), String> {
+        Err(self.reason.clone())
+    }
+}
@@ -0,0 +1,117 @@
+mod crypto;
+mod json;
+mod lock;
+mod locked;
+mod sqlite;
+
+use std::path::PathBuf;
+use chrono::NaiveDate;
+use crate::persistence::{History, HistoryEntry, SessionRecord};
+use crate::settings::{RetentionPolicy, Settings, StorageBackend};
+use crate::timer::TimerState;
+
+pub use crypto::Cipher;
+pub use json::JsonStore;
+pub use locked::LockedStore;
+pub use sqlite::SqliteStore;
+
+/// Where timer state and session history live.
+///
+/// Implementations panic on I/O failure, matching the rest of the persistence layer.
+pub trait Store: Send + Sync {
+    fn load_state(&self) -> TimerState;
+    fn save_state(&self, state: &TimerState);
+
+    /// Full history: every daily aggregate and session record.
+    fn load_history(&self) -> History;
+    /// Daily aggregates with `from <= date <= to` (YYYY-MM-DD), oldest first.
+    fn history_between(&self, from: &str, to: &str) -> Vec<HistoryEntry>;
+    fn log_session(&self, record: &SessionRecord);
+    /// Add the days from `history` that aren't stored yet; returns how many were added.
+    fn merge_history(&self, history: History) -> usize;
+
+    /// Archive, compact and expire old history. Safe to run repeatedly.
+    fn apply_retention(&self, policy: &RetentionPolicy, today: NaiveDate);
+
+    /// Rewrite all history in place with `cipher`, or as plaintext for `None`,
+    /// and keep using it for later writes.
+    fn set_encryption(&self, cipher: Option<Cipher>) -> Result<(), String>;
+}
+
+/// The first date (YYYY-MM-DD) still inside a window of `days` days ending today.
+pub(crate) fn cutoff(today: NaiveDate, days: u32) -> String {
+    (today - chrono::Duration::days(days as i64)).format("%Y-%m-%d").to_string()
+}
+
+/// The backend from `settings`. Fails when history can't be read: no usable key (passphrase
+/// not set, keyfile missing, wrong key, or encrypted files and no key at all), or a damaged file.
+pub fn open(settings: &Settings, dir: PathBuf) -> Result<Box<dyn Store>, String> {
+    let cipher = settings.encryption.as_ref().map(|key| Cipher::load(key, &dir)).transpose();
+    match settings.storage {
+        StorageBackend::Json => {
+            let store = JsonStore::with_cipher(dir, cipher?);
+            store.check_readable()?;
+            Ok(Box::new(store))
+        }
+        StorageBackend::Sqlite => {
+            if settings.encryption.is_some() {
+                eprintln!("History encryption applies to the JSON backend only; history.db stays plaintext");
+            }
+            let store = SqliteStore::open(dir.to_path_buf());
+            // First switch to SQLite: carry over whatever the JSON backend recorded.
+            if !store.has_imported_json() {
+                let json = JsonStore::with_cipher(dir, cipher?);
+                json.check_readable()?;
+                store.import_json(&json.load_history());
+            }
+            Ok(Box::new(store))
+        }
+    }
+}
+
+/// What to use when `open` fails: the usual `state.json`, and no history.
+pub fn locked(dir: PathBuf, reason: String) -> Box<dyn Store> {
+    Box::new(LockedStore::new(Box::new(JsonStore::new(dir)), reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::SessionRecord;
+    use crate::settings::EncryptionKey;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn test_open_without_the_key() {
+        let dir = TempDir::new("open");
+        let key = EncryptionKey::Passphrase { env: "POMODORO_TEST_UNSET_PASSPHRASE".to_string() };
+        let settings = Settings { encryption: Some(key), ..Settings::default() };
+        let error = open(&settings, dir.to_path_buf()).err().unwrap();
+        assert!(error.contains("POMODORO_TEST_UNSET_PASSPHRASE"));
+
+        // Encrypted history and no key configured at all
+        let cipher = Cipher::derive(b"lotus", b"0123456789abcdef").unwrap();
+        std::fs::write(dir.join("history.json"), cipher.encrypt(b"{}")).unwrap();
+        assert!(open(&Settings::default(), dir.to_path_buf()).is_err());
+
+        let store = locked(dir.to_path_buf(), "no key".to_string());
+        let mut state = TimerState::default();
+        state.start_focus(600);
+        store.save_state(&state);
+        assert_eq!(store.load_state(), state);
+        store.log_session(&SessionRecord::focus("2026-03-01", 25));
+        assert!(store.load_history().sessions.is_empty());
+        // Left untouched for when the key is back
+        assert_eq!(cipher.decrypt(&std::fs::read(dir.join("history.json")).unwrap()).unwrap(), b"{}");
+    }
+
+    #[test]
+    fn test_open_with_damaged_history() {
+        let dir = TempDir::new("open-damaged");
+        std::fs::create_dir(dir.join("archive")).unwrap();
+        std::fs::write(dir.join("archive").join("history-2024.json"), "{\"sessions\": [").unwrap();
+        let error = open(&Settings::default(), dir.to_path_buf()).err().unwrap();
+        assert!(error.contains("history-2024.json"), "{}", error);
+        assert_eq!(std::fs::read_to_string(dir.join("archive").join("history-2024.json")).unwrap(), "{\"sessions\": [");
+    }
+}
@@ -0,0 +1,278 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+use chrono::NaiveDate;
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::persistence::{History, HistoryEntry, Phase, SessionRecord};
+use crate::settings::RetentionPolicy;
+use crate::timer::TimerState;
+use super::{cutoff, Cipher, JsonStore, Store};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS days (
+        date TEXT PRIMARY KEY,
+        completed INTEGER NOT NULL DEFAULT 0,
+        forfeited INTEGER NOT NULL DEFAULT 0,
+        total_focus_minutes INTEGER NOT NULL DEFAULT 0
+    );
+    CREATE TABLE IF NOT EXISTS sessions (
+        id INTEGER PRIMARY KEY,
+        date TEXT NOT NULL,
+        ended_at INTEGER NOT NULL,
+        minutes INTEGER NOT NULL,
+        completed INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS sessions_date ON sessions(date);
+    CREATE TABLE IF NOT EXISTS meta (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+";
+
+/// Applied in order on open; `PRAGMA user_version` records how many have run.
+const MIGRATIONS: &[&str] = &[
+    "ALTER TABLE sessions ADD COLUMN label TEXT;
+     ALTER TABLE sessions ADD COLUMN tags TEXT NOT NULL DEFAULT '';",
+    "ALTER TABLE sessions ADD COLUMN phase TEXT NOT NULL DEFAULT 'focus';",
+    "ALTER TABLE sessions ADD COLUMN reason TEXT;",
+];
+
+fn migrate(conn: &Connection) {
+    let version: usize = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .expect("Could not read history.db version");
+    for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        conn.execute_batch(migration).expect("Could not migrate history.db");
+        conn.pragma_update(None, "user_version", i + 1).expect("Could not migrate history.db");
+    }
+}
+
+// Tags are stored comma-separated; they never contain commas (see `export::normalize_tag`).
+fn join_tags(tags: &[String]) -> String {
+    tags.join(",")
+}
+
+fn split_tags(tags: String) -> Vec<String> {
+    tags.split(',').filter(|t| !t.is_empty()).map(str::to_string).collect()
+}
+
+fn insert_session(conn: &Connection, record: &SessionRecord) {
+    conn.execute(
+        "INSERT INTO sessions (date, ended_at, minutes, completed, label, tags, phase, reason) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![record.date, record.ended_at, record.minutes, record.completed, record.label, join_tags(&record.tags), record.phase.as_str(), record.reason],
+    ).expect("Could not insert session");
+}
+
+/// History in `~/.pomodoro/history.db`.
+///
+/// Timer state stays in `state.json` so the SwiftBar script and other tools
+/// reading it keep working; only history moves into the database.
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+    state: JsonStore,
+}
+
+impl SqliteStore {
+    pub fn open(dir: PathBuf) -> Self {
+        let conn = Connection::open(dir.join("history.db")).expect("Could not open history.db");
+        Self::with_connection(conn, dir)
+    }
+
+    pub fn with_connection(conn: Connection, dir: PathBuf) -> Self {
+        conn.execute_batch(SCHEMA).expect("Could not create history.db schema");
+        migrate(&conn);
+        Self {
+            conn: Mutex::new(conn),
+            state: JsonStore::new(dir),
+        }
+    }
+
+    pub fn has_imported_json(&self) -> bool {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row("SELECT value FROM meta WHERE key = 'imported_json'", [], |row| row.get::<_, String>(0))
+            .optional()
+            .expect("Could not read history.db")
+            .is_some()
+    }
+
+    /// Copy a JSON history into the database. Days already present are left untouched,
+    /// so running the import twice does not double-count.
+    pub fn import_json(&self, history: &History) {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().expect("Could not start import");
+        insert_missing_days(&tx, history);
+        tx.execute("INSERT OR REPLACE INTO meta (key, value) VALUES ('imported_json', '1')", [])
+            .expect("Could not mark import");
+        tx.commit().expect("Could not commit import");
+    }
+}
+
+fn insert_missing_days(conn: &Connection, history: &History) -> usize {
+    let mut added = 0;
+    for entry in &history.sessions {
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO days (date, completed, forfeited, total_focus_minutes) VALUES (?1, ?2, ?3, ?4)",
+            params![entry.date, entry.completed, entry.forfeited, entry.total_focus_minutes],
+        ).expect("Could not import day");
+        if inserted == 0 {
+            continue;
+        }
+        added += 1;
+        for record in history.records.iter().filter(|r| r.date == entry.date) {
+            insert_session(conn, record);
+        }
+    }
+    added
+}
+
+fn read_days(conn: &Connection, from: &str, to: &str) -> Vec<HistoryEntry> {
+    let mut stmt = conn
+        .prepare("SELECT date, completed, forfeited, total_focus_minutes FROM days WHERE date BETWEEN ?1 AND ?2 ORDER BY date")
+        .expect("Could not query history.db");
+    stmt.query_map([from, to], |row| {
+        Ok(HistoryEntry {
+            date: row.get(0)?,
+            completed: row.get(1)?,
+            forfeited: row.get(2)?,
+            total_focus_minutes: row.get(3)?,
+        })
+    })
+    .expect("Could not query history.db")
+    .collect::<Result<_, _>>()
+    .expect("Could not read history.db")
+}
+
+impl Store for SqliteStore {
+    fn load_state(&self) -> TimerState {
+        self.state.load_state()
+    }
+
+    fn save_state(&self, state: &TimerState) {
+        self.state.save_state(state);
+    }
+
+    fn load_history(&self) -> History {
+        let conn = self.conn.lock().unwrap();
+        let sessions = read_days(&conn, "", "9999-12-31");
+        let mut stmt = conn
+            .prepare("SELECT date, ended_at, minutes, completed, label, tags, phase, reason FROM sessions ORDER BY ended_at")
+            .expect("Could not query history.db");
+        let records = stmt
+            .query_map([], |row| {
+                Ok(SessionRecord {
+                    date: row.get(0)?,
+                    ended_at: row.get(1)?,
+                    minutes: row.get(2)?,
+                    completed: row.get(3)?,
+                    phase: Phase::parse(&row.get::<_, String>(6)?),
+                    label: row.get(4)?,
+                    tags: split_tags(row.get(5)?),
+                    reason: row.get(7)?,
+                })
+            })
+            .expect("Could not query history.db")
+            .collect::<Result<_, _>>()
+            .expect("Could not read history.db");
+        History { sessions, records }
+    }
+
+    fn history_between(&self, from: &str, to: &str) -> Vec<HistoryEntry> {
+        read_days(&self.conn.lock().unwrap(), from, to)
+    }
+
+    fn log_session(&self, record: &SessionRecord) {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().expect("Could not start transaction");
+        let (completed, forfeited, minutes) = record.day_totals();
+        tx.execute(
+            "INSERT INTO days (date, completed, forfeited, total_focus_minutes) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(date) DO UPDATE SET
+                completed = completed + excluded.completed,
+                forfeited = forfeited + excluded.forfeited,
+                total_focus_minutes = total_focus_minutes + excluded.total_focus_minutes",
+            params![record.date, completed, forfeited, minutes],
+        ).expect("Could not log session");
+        insert_session(&tx, record);
+        tx.commit().expect("Could not commit session");
+    }
+
+    fn merge_history(&self, history: History) -> usize {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().expect("Could not start import");
+        let added = insert_missing_days(&tx, &history);
+        tx.commit().expect("Could not commit import");
+        added
+    }
+
+    fn set_encryption(&self, _cipher: Option<Cipher>) -> Result<(), String> {
+        Err("History encryption is only supported by the JSON backend".to_string())
+    }
+
+    // Archiving is a JSON concern: the indexed tables stay fast at any size.
+    fn apply_retention(&self, policy: &RetentionPolicy, today: NaiveDate) {
+        let conn = self.conn.lock().unwrap();
+        if let Some(days) = policy.max_age_days {
+            let cutoff = cutoff(today, days);
+            conn.execute("DELETE FROM days WHERE date < ?1", [&cutoff]).expect("Could not expire history");
+            conn.execute("DELETE FROM sessions WHERE date < ?1", [&cutoff]).expect("Could not expire history");
+        }
+        if let Some(days) = policy.compact_after_days {
+            conn.execute("DELETE FROM sessions WHERE date < ?1", [cutoff(today, days)])
+                .expect("Could not compact history");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory_store() -> SqliteStore {
+        SqliteStore::with_connection(Connection::open_in_memory().unwrap(), std::env::temp_dir())
+    }
+
+    fn record(date: &str, minutes: u32, completed: bool) -> SessionRecord {
+        SessionRecord { completed, tags: vec!["deep".to_string()], ..SessionRecord::focus(date, minutes) }
+    }
+
+    #[test]
+    fn test_log_session_aggregates_by_day() {
+        let store = memory_store();
+        store.log_session(&record("2026-02-01", 25, true));
+        store.log_session(&record("2026-02-01", 10, false));
+        store.log_session(&record("2026-02-02", 45, true));
+
+        let days = store.history_between("2026-02-01", "2026-02-28");
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].completed, 1);
+        assert_eq!(days[0].forfeited, 1);
+        assert_eq!(days[0].total_focus_minutes, 25);
+        assert_eq!(store.history_between("2026-02-02", "2026-02-28").len(), 1);
+        let records = store.load_history().records;
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].tags, vec!["deep".to_string()]);
+
+        // Breaks are kept as records but never count as focus
+        store.log_session(&SessionRecord { phase: Phase::SkippedBreak, ..record("2026-02-01", 2, false) });
+        let days = store.history_between("2026-02-01", "2026-02-01");
+        assert_eq!((days[0].completed, days[0].forfeited, days[0].total_focus_minutes), (1, 1, 25));
+        assert_eq!(store.load_history().records.last().unwrap().phase, Phase::SkippedBreak);
+    }
+
+    #[test]
+    fn test_import_json_is_idempotent() {
+        let mut history = History::default();
+        history.apply(&record("2026-01-30", 25, true));
+        history.apply(&record("2026-01-31", 25, true));
+
+        let store = memory_store();
+        assert!(!store.has_imported_json());
+        store.import_json(&history);
+        store.import_json(&history);
+        assert!(store.has_imported_json());
+
+        let imported = store.load_history();
+        assert_eq!(imported.sessions.len(), 2);
+        assert_eq!(imported.records.len(), 2);
+        assert_eq!(imported.sessions[1].total_focus_minutes, 25);
+    }
+}
@@ -0,0 +1,50 @@
+//! Fixtures shared by the test modules.
+
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use crate::persistence::{Phase, SessionRecord};
+
+impl SessionRecord {
+    /// A completed focus session of `minutes` on `date` (YYYY-MM-DD), with nothing else set.
+    pub fn focus(date: &str, minutes: u32) -> Self {
+        Self {
+            date: date.to_string(),
+            ended_at: 0,
+            minutes,
+            completed: true,
+            phase: Phase::Focus,
+            label: None,
+            tags: Vec::new(),
+            reason: None,
+        }
+    }
+}
+
+/// An empty scratch directory, removed with everything in it on drop.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let id = NEXT.fetch_add(1, Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!("pomodoro-{}-{}-{}", name, std::process::id(), id));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
@@ -18,7 +18,35 @@ pub enum TimerType {
     Calm,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Durations (seconds) the timer falls back to. Comes from settings, not from state.json.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimerConfig {
+    pub focus: u64,
+    pub short_break: u64,
+    pub long_break: u64,
+    pub long_break_every: u32,     // focus sessions per long break, 0 = never
+    pub micro_break_interval: u64, // 0 = micro-breaks off
+    pub calm: u64,
+}
+
+impl Default for TimerConfig {
+    fn default() -> Self {
+        Self {
+            focus: 1500,
+            short_break: 300,
+            long_break: 900,
+            long_break_every: 4,
+            micro_break_interval: 300,
+            calm: 300,
+        }
+    }
+}
+
+/// Seconds added by one "extend focus"
+pub const EXTENSION: u64 = 300;
+pub const MAX_EXTENSIONS: u8 = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct TimerState {
     pub status: Status,
     pub remaining: u64,  // seconds
@@ -35,6 +63,13 @@ pub struct TimerState {
     pub extensions: u8,
     #[serde(default)]
     pub last_micro_break: u64,  // Track last micro-break timestamp (elapsed seconds into session)
+    /// Quick-capture note for the current (or next) focus session, recorded with it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip)]
+    pub config: TimerConfig,
 }
 
 impl Default for TimerState {
@@ -50,6 +85,9 @@ impl Default for TimerState {
             earned_break: 0,
             extensions: 0,
             last_micro_break: 0,
+            label: None,
+            tags: Vec::new(),
+            config: TimerConfig::default(),
         }
     }
 }
@@ -100,10 +138,11 @@ impl TimerState {
         result
     }
     
-    /// Check if micro-break should trigger (every 5 minutes during focus)
+    /// Check if micro-break should trigger (every `micro_break_interval` during focus)
     /// Returns true if a micro-break should be shown
     pub fn should_trigger_micro_break(&mut self) -> bool {
-        if self.status != Status::Focus || self.timer_type != TimerType::Focus {
+        let interval = self.config.micro_break_interval;
+        if interval == 0 || self.status != Status::Focus || self.timer_type != TimerType::Focus {
             return false;
         }
         
@@ -111,11 +150,10 @@ impl TimerState {
             let now = current_timestamp();
             let elapsed = now.saturating_sub(start);
             
-            // Trigger every 5 minutes (300 seconds)
-            let current_interval = elapsed / 300;
-            let last_interval = self.last_micro_break / 300;
+            let current_interval = elapsed / interval;
+            let last_interval = self.last_micro_break / interval;
             
-            // Only trigger if we've crossed into a new 5-minute interval
+            // Only trigger if we've crossed into a new interval
             // and we're not in the last 30 seconds of the session (to avoid overlap with full break)
             if current_interval > last_interval && self.remaining > 30 {
                 self.last_micro_break = elapsed;
@@ -145,24 +183,81 @@ impl TimerState {
                 // Transition to break or idle
                 self.status = Status::Idle; // For now. Later Phase 3 will trigger Sanctuary
                 self.start_time = None;
-                self.remaining = 300; // Suggest 5m break
-                self.total = 300;
+                let suggested = self.suggested_break();
+                self.earned_break = 0;
+                self.extensions = 0;
+                self.remaining = suggested;
+                self.total = suggested;
                 self.timer_type = TimerType::Break;
             }
             _ => {
                 self.status = Status::Idle;
                 self.start_time = None;
-                self.remaining = 1500;
-                self.total = 1500;
+                self.remaining = self.config.focus;
+                self.total = self.config.focus;
                 self.timer_type = TimerType::Focus;
             }
         }
     }
 
+    /// Suggested break after the focus sessions done today: long one every `long_break_every`
+    /// sessions, plus whatever was earned by extending.
+    fn suggested_break(&self) -> u64 {
+        let every = self.config.long_break_every;
+        let done = self.sessions_today;
+        let base = if every > 0 && done > 0 && done.is_multiple_of(every) {
+            self.config.long_break
+        } else {
+            self.config.short_break
+        };
+        base + self.earned_break
+    }
+
+    /// A break is running, paused, or waiting to be started after a finished focus session.
+    pub fn has_break(&self) -> bool {
+        self.timer_type != TimerType::Focus
+    }
+
+    /// Start the break now: the waiting one if a focus session just finished, otherwise a
+    /// short break (plus anything earned), cutting a running focus session short.
+    /// Returns false when a break is already running or paused.
+    pub fn start_break_now(&mut self) -> bool {
+        let duration = match (&self.status, &self.timer_type) {
+            (Status::Idle, TimerType::Break) => self.remaining,
+            // The session is not finished, so it doesn't move the long break closer
+            (_, TimerType::Focus) => self.config.short_break + self.earned_break,
+            _ => return false,
+        };
+        self.earned_break = 0;
+        self.extensions = 0;
+        self.start_break(duration);
+        true
+    }
+
+    /// End the break early and get ready for the next focus session.
+    /// Returns the minutes of break actually taken, or `None` when there is no break to skip.
+    pub fn skip_break(&mut self) -> Option<u32> {
+        if !self.has_break() {
+            return None;
+        }
+        let left = match (&self.status, self.start_time) {
+            (Status::Idle, _) => self.total,
+            (Status::Break, Some(start)) => self.total.saturating_sub(current_timestamp().saturating_sub(start)),
+            _ => self.remaining,
+        };
+        let taken = self.total.saturating_sub(left) / 60;
+        self.status = Status::Idle;
+        self.start_time = None;
+        self.timer_type = TimerType::Focus;
+        self.remaining = self.config.focus;
+        self.total = self.config.focus;
+        Some(taken as u32)
+    }
+
     /// Toggle between start/pause/resume based on current status
     pub fn toggle(&mut self) {
         match self.status {
-            Status::Idle => self.start_focus(1500),
+            Status::Idle => self.start_focus(self.config.focus),
             Status::Paused => self.resume(),
             _ => self.pause(),
         }
@@ -175,6 +270,28 @@ impl TimerState {
         self.remaining = duration;
         self.start_time = Some(current_timestamp());
         self.last_micro_break = 0; // Reset micro-break tracker
+        self.extensions = 0;
+        self.earned_break = 0;
+    }
+
+    /// Hand the quick-capture note to the session being recorded; the next session starts blank.
+    pub fn take_capture(&mut self) -> (Option<String>, Vec<String>) {
+        (self.label.take(), std::mem::take(&mut self.tags))
+    }
+
+    /// In the zone: +5 minutes of focus, earning +1 minute of break, at most `MAX_EXTENSIONS` times.
+    /// Returns false when there is no focus session to extend or the limit is reached.
+    pub fn extend(&mut self) -> bool {
+        let focusing = self.timer_type == TimerType::Focus
+            && matches!(self.status, Status::Focus | Status::Paused);
+        if !focusing || self.extensions >= MAX_EXTENSIONS {
+            return false;
+        }
+        self.total += EXTENSION;
+        self.remaining += EXTENSION;
+        self.extensions += 1;
+        self.earned_break += EXTENSION / 5;
+        true
     }
 
     pub fn start_break(&mut self, duration: u64) {
@@ -188,15 +305,17 @@ impl TimerState {
     pub fn forfeit(&mut self) {
         self.status = Status::Idle;
         self.start_time = None;
-        self.remaining = 1500;
-        self.total = 1500;
+        self.extensions = 0;
+        self.earned_break = 0;
+        self.remaining = self.config.focus;
+        self.total = self.config.focus;
     }
 
     pub fn reset(&mut self) {
         self.status = Status::Idle;
         self.start_time = None;
-        self.remaining = 1500;
-        self.total = 1500;
+        self.remaining = self.config.focus;
+        self.total = self.config.focus;
     }
 
     fn pause(&mut self) {
@@ -210,9 +329,10 @@ impl TimerState {
             TimerType::Focus => Status::Focus,
             TimerType::Break | TimerType::Calm => Status::Break,
         };
-        // Accurate resume: subtract consumed time from total
-        self.start_time = Some(current_timestamp());
-        self.total = self.remaining; 
+        // Backdate the start by the time already spent, so `total` stays the whole session
+        // (progress, forfeit minutes and micro-breaks all count from the real start)
+        let spent = self.total.saturating_sub(self.remaining);
+        self.start_time = Some(current_timestamp().saturating_sub(spent));
     }
 
     /// Format remaining time as Monospace Unicode MM:SS
@@ -242,7 +362,7 @@ impl TimerState {
     }
 }
 
-fn convert_to_active_monospace(s: &str) -> String {
+pub fn convert_to_active_monospace(s: &str) -> String {
     s.chars()
         .map(|c| match c {
             '0' => '𝟶',
@@ -282,8 +402,7 @@ mod tests {
 
     #[test]
     fn test_finish_focus_session() {
-        let mut state = TimerState::default();
-        state.timer_type = TimerType::Focus;
+        let mut state = TimerState { timer_type: TimerType::Focus, ..Default::default() };
         state.finish_session();
         assert_eq!(state.sessions_today, 1);
         assert_eq!(state.status, Status::Idle);
@@ -291,6 +410,21 @@ mod tests {
         assert_eq!(state.remaining, 300);
     }
 
+    #[test]
+    fn test_long_break_after_configured_sessions() {
+        let mut state = TimerState {
+            sessions_today: 1,
+            config: TimerConfig { long_break_every: 2, long_break: 1200, ..Default::default() },
+            ..Default::default()
+        };
+        state.finish_session();
+        assert_eq!(state.remaining, 1200);
+
+        state.timer_type = TimerType::Focus;
+        state.finish_session();
+        assert_eq!(state.remaining, 300);
+    }
+
     #[test]
     fn test_date_reset() {
         let mut state = TimerState {
@@ -313,11 +447,58 @@ mod tests {
             start_time: Some(current_timestamp() - 1500),
             ..Default::default()
         };
-        let result = state.calculate_remaining();
+        let result = state.calculate_remaining(false);
         assert!(result.is_some());
         let (completed, minutes) = result.unwrap();
         assert!(completed);
         assert_eq!(minutes, 25);
         assert_eq!(state.status, Status::Idle);
     }
+
+    #[test]
+    fn test_extend_earns_break_up_to_limit() {
+        let mut state = TimerState::default();
+        assert!(!state.extend());
+
+        state.start_focus(1500);
+        for _ in 0..MAX_EXTENSIONS {
+            assert!(state.extend());
+        }
+        assert!(!state.extend());
+        assert_eq!(state.total, 1500 + 25 * 60);
+        assert_eq!(state.earned_break, 300);
+
+        state.finish_session();
+        assert_eq!(state.remaining, 300 + 300);
+        assert_eq!(state.extensions, 0);
+        assert_eq!(state.earned_break, 0);
+    }
+
+    #[test]
+    fn test_start_and_skip_break() {
+        let mut state = TimerState::default();
+        state.reset();
+        assert_eq!(state.skip_break(), None);
+
+        // Cutting a focus session short starts a short break, earned minutes included
+        state.start_focus(1500);
+        state.extend();
+        assert!(state.start_break_now());
+        assert_eq!((state.status.clone(), state.timer_type.clone()), (Status::Break, TimerType::Break));
+        assert_eq!(state.total, 300 + 60);
+        assert!(!state.start_break_now());
+
+        // Skipping gets the next focus session ready
+        assert_eq!(state.skip_break(), Some(0));
+        assert_eq!((state.status.clone(), state.timer_type.clone()), (Status::Idle, TimerType::Focus));
+        assert_eq!(state.remaining, 1500);
+
+        // After the 4th session the waiting break is the long one
+        state.sessions_today = 3;
+        state.start_focus(1500);
+        state.finish_session();
+        assert!(state.has_break());
+        assert!(state.start_break_now());
+        assert_eq!(state.total, 900);
+    }
 }
@@ -0,0 +1,320 @@
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Duration, Local};
+use crate::i18n;
+use crate::timer::{convert_to_active_monospace, Status, TimerState, TimerType};
+
+/// Placeholders a tray title template may use.
+pub const TITLE_PLACEHOLDERS: &[&str] = &["icon", "time", "minutes", "sessions", "goal"];
+
+/// How numbers in the tray title are drawn.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TitleDigits {
+    /// Unicode mathematical monospace digits, so the title does not jitter as it counts down.
+    #[default]
+    Monospace,
+    /// Plain ASCII, for panels whose fonts lack the monospace digits.
+    Ascii,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TitleFormat {
+    pub template: String,
+    pub digits: TitleDigits,
+    /// Focus sessions per day for `{goal}`; 0 leaves `{goal}` empty.
+    pub daily_goal: u32,
+}
+
+impl Default for TitleFormat {
+    fn default() -> Self {
+        Self {
+            template: "{icon} {time}".to_string(),
+            digits: TitleDigits::default(),
+            daily_goal: 8,
+        }
+    }
+}
+
+/// Names inside `{...}` that are not placeholders.
+pub fn unknown_placeholders(template: &str) -> Vec<String> {
+    let mut unknown = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else { break };
+        let name = &rest[start + 1..start + len];
+        if !TITLE_PLACEHOLDERS.contains(&name) {
+            unknown.push(name.to_string());
+        }
+        rest = &rest[start + len + 1..];
+    }
+    unknown
+}
+
+/// Render the tray title, e.g. `"{icon} {time}"` → `"⦿ 𝟸𝟹:𝟺𝟻"`.
+pub fn title(format: &TitleFormat, state: &TimerState) -> String {
+    let number = |text: String| match format.digits {
+        TitleDigits::Monospace => convert_to_active_monospace(&text),
+        TitleDigits::Ascii => text,
+    };
+    let time = format!("{:02}:{:02}", state.remaining / 60, state.remaining % 60);
+    let goal = if format.daily_goal > 0 {
+        number(format!("{}/{}", state.sessions_today, format.daily_goal))
+    } else {
+        String::new()
+    };
+    format
+        .template
+        .replace("{icon}", state.get_icon())
+        .replace("{time}", &number(time))
+        .replace("{minutes}", &number(state.remaining.div_ceil(60).to_string()))
+        .replace("{sessions}", &number(state.sessions_today.to_string()))
+        .replace("{goal}", &goal)
+        .trim()
+        .to_string()
+}
+
+/// Hover text for the tray: phase and label, time left and end time, progress toward the
+/// long break and `focus_minutes` done today. Minute precision, so it changes at most once a minute.
+pub fn tooltip(state: &TimerState, focus_minutes: u32, now: DateTime<Local>) -> String {
+    let phase = match (&state.status, &state.timer_type) {
+        (Status::Idle, _) => i18n::tr("tooltip-idle"),
+        (Status::Paused, _) => i18n::tr("tooltip-paused"),
+        (_, TimerType::Focus) => i18n::tr("tooltip-focus"),
+        (_, TimerType::Break) => i18n::tr("tooltip-break"),
+        (_, TimerType::Calm) => i18n::tr("tooltip-calm"),
+    };
+    let mut lines = vec![match &state.label {
+        Some(label) => format!("{} · {}", phase, label),
+        None => phase,
+    }];
+
+    let minutes = state.remaining.div_ceil(60);
+    match state.status {
+        Status::Focus | Status::Break => {
+            let end = now + Duration::seconds(state.remaining as i64);
+            lines.push(i18n::tr_args("tooltip-remaining", &[
+                ("minutes", minutes.into()),
+                ("end", end.format("%H:%M").to_string().into()),
+            ]));
+        }
+        Status::Paused => lines.push(i18n::tr_args("tooltip-remaining-paused", &[("minutes", minutes.into())])),
+        Status::Idle => {}
+    }
+
+    let every = state.config.long_break_every;
+    if every > 0 {
+        let count = every - state.sessions_today % every;
+        lines.push(i18n::tr_args("tooltip-long-break", &[("count", count.into())]));
+    }
+    lines.push(i18n::tr_args("tooltip-focus-today", &[("minutes", focus_minutes.into())]));
+    lines.join("\n")
+}
+
+/// How the tray icon shows progress.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IconStyle {
+    /// A ring that empties clockwise as the session runs down.
+    #[default]
+    Ring,
+    /// A filled pie slice instead of a ring.
+    Pie,
+    /// The app icon; state is shown by the `{icon}` glyph in the title.
+    Glyph,
+}
+
+/// Icon edge in pixels. Trays scale it down, so draw at 2x for HiDPI panels.
+pub const ICON_SIZE: u32 = 44;
+/// Distinct progress pictures per session (~1 per minute of a 25 minute focus).
+pub const ICON_STEPS: u64 = 24;
+
+/// Everything the icon depends on. The icon is only redrawn when this changes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IconKey {
+    pub status: Status,
+    pub step: u64,  // 0..=ICON_STEPS of the ring still to go
+}
+
+pub fn icon_key(state: &TimerState) -> IconKey {
+    let step = match state.status {
+        Status::Idle => ICON_STEPS,
+        _ if state.total == 0 => 0,
+        _ => (state.remaining * ICON_STEPS).div_ceil(state.total).min(ICON_STEPS),
+    };
+    IconKey { status: state.status.clone(), step }
+}
+
+/// Temple stone when idle, terracotta for focus, sage for breaks, dimmed when paused.
+fn status_color(status: &Status) -> [u8; 3] {
+    match status {
+        Status::Idle => [0x8B, 0x73, 0x55],
+        Status::Focus => [0xC6, 0x7B, 0x5C],
+        Status::Break => [0x7D, 0x84, 0x71],
+        Status::Paused => [0xAA, 0xAA, 0xAA],
+    }
+}
+
+/// Opacity of the part of the ring already used up.
+const TRACK_ALPHA: f64 = 0.3;
+/// Subpixel grid per pixel edge, for anti-aliasing.
+const SUPERSAMPLE: u32 = 4;
+
+/// Straight (non-premultiplied) RGBA, `size * size * 4` bytes. `None` for `IconStyle::Glyph`.
+pub fn render_icon(style: IconStyle, key: &IconKey, size: u32) -> Option<Vec<u8>> {
+    let inner_ratio = match style {
+        IconStyle::Ring => 0.62,
+        IconStyle::Pie => 0.0,
+        IconStyle::Glyph => return None,
+    };
+    let [r, g, b] = status_color(&key.status);
+    let outer = size as f64 / 2.0 - 1.0;
+    let inner = outer * inner_ratio;
+    let sweep = std::f64::consts::TAU * key.step as f64 / ICON_STEPS as f64;
+    let center = size as f64 / 2.0;
+    let samples = (SUPERSAMPLE * SUPERSAMPLE) as f64;
+
+    let mut pixels = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let mut coverage = 0.0;
+            for sy in 0..SUPERSAMPLE {
+                for sx in 0..SUPERSAMPLE {
+                    let dx = x as f64 + (sx as f64 + 0.5) / SUPERSAMPLE as f64 - center;
+                    let dy = y as f64 + (sy as f64 + 0.5) / SUPERSAMPLE as f64 - center;
+                    let distance = dx.hypot(dy);
+                    if distance > outer || distance < inner {
+                        continue;
+                    }
+                    // Clockwise from 12 o'clock
+                    let angle = dx.atan2(-dy).rem_euclid(std::f64::consts::TAU);
+                    coverage += if angle < sweep { 1.0 } else { TRACK_ALPHA };
+                }
+            }
+            let alpha = (coverage / samples * 255.0).round() as u8;
+            pixels.extend_from_slice(&[r, g, b, alpha]);
+        }
+    }
+    Some(pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn state() -> TimerState {
+        TimerState {
+            status: Status::Focus,
+            remaining: 1385,
+            sessions_today: 3,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_default_title_matches_previous_format() {
+        let state = state();
+        assert_eq!(title(&TitleFormat::default(), &state), format!("{} {}", state.get_icon(), state.format_time()));
+    }
+
+    #[test]
+    fn test_templates_and_ascii_digits() {
+        let format = |template: &str| TitleFormat {
+            template: template.to_string(),
+            digits: TitleDigits::Ascii,
+            daily_goal: 8,
+        };
+        assert_eq!(title(&format("{icon}"), &state()), "⦿");
+        assert_eq!(title(&format("{minutes}m"), &state()), "24m");
+        assert_eq!(title(&format("{time} · {goal}"), &state()), "23:05 · 3/8");
+        assert_eq!(title(&format("{icon} {sessions}"), &state()), "⦿ 3");
+
+        let no_goal = TitleFormat { daily_goal: 0, ..format("{icon} {goal}") };
+        assert_eq!(title(&no_goal, &state()), "⦿");
+    }
+
+    #[test]
+    fn test_unknown_placeholders() {
+        assert!(unknown_placeholders("{icon} {time} {goal}").is_empty());
+        assert_eq!(unknown_placeholders("{icon} {secs} {"), vec!["secs".to_string()]);
+    }
+
+    fn alpha_at(pixels: &[u8], x: u32, y: u32) -> u8 {
+        pixels[((y * ICON_SIZE + x) * 4 + 3) as usize]
+    }
+
+    #[test]
+    fn test_icon_key_changes_in_steps() {
+        let mut state = state();
+        state.total = 1500;
+        state.remaining = 1500;
+        assert_eq!(icon_key(&state).step, ICON_STEPS);
+        state.remaining = 1490;
+        assert_eq!(icon_key(&state), IconKey { status: Status::Focus, step: ICON_STEPS });
+        state.remaining = 750;
+        assert_eq!(icon_key(&state).step, ICON_STEPS / 2);
+        state.remaining = 0;
+        assert_eq!(icon_key(&state).step, 0);
+    }
+
+    #[test]
+    fn test_icon_key_keeps_progress_across_pause() {
+        let mut state = state();
+        state.start_focus(1500);
+        state.start_time = state.start_time.map(|t| t - 600);
+        state.toggle();
+        assert_eq!((&state.status, state.remaining), (&Status::Paused, 900));
+        let paused = icon_key(&state).step;
+        assert_eq!(paused, (900 * ICON_STEPS).div_ceil(1500));
+
+        state.toggle();
+        state.calculate_remaining(false);
+        assert_eq!(state.status, Status::Focus);
+        assert_eq!(icon_key(&state).step, paused);
+    }
+
+    #[test]
+    fn test_ring_empties_clockwise() {
+        let key = IconKey { status: Status::Focus, step: ICON_STEPS / 2 };
+        let pixels = render_icon(IconStyle::Ring, &key, ICON_SIZE).unwrap();
+        assert_eq!(pixels.len(), (ICON_SIZE * ICON_SIZE * 4) as usize);
+        assert_eq!(&pixels[0..3], &[0xC6, 0x7B, 0x5C]);
+
+        let mid = ICON_SIZE / 2;
+        let ring = ICON_SIZE - 5;  // inside the ring band on the right/bottom edge
+        assert_eq!(alpha_at(&pixels, ring, mid), 255);      // 3 o'clock: still to go
+        let track = alpha_at(&pixels, ICON_SIZE - ring, mid);  // 9 o'clock: used up
+        assert!(track > 0 && track < 128, "track alpha {}", track);
+        assert_eq!(alpha_at(&pixels, mid, mid), 0);         // hole
+        assert_eq!(alpha_at(&pixels, 0, 0), 0);             // corner
+
+        let pie = render_icon(IconStyle::Pie, &key, ICON_SIZE).unwrap();
+        assert_eq!(alpha_at(&pie, mid + 2, mid + 2), 255);
+        assert!(render_icon(IconStyle::Glyph, &key, ICON_SIZE).is_none());
+    }
+
+    #[test]
+    fn test_tooltip_details() {
+        i18n::set_locale(Some("en-US"));
+        let now = Local.with_ymd_and_hms(2026, 3, 2, 9, 30, 0).unwrap();
+        let mut state = state();
+        state.label = Some("Draft report".to_string());
+        state.config.long_break_every = 4;
+        assert_eq!(
+            tooltip(&state, 75, now),
+            "Focus · Draft report\n24 min left · ends 09:53\n1 session until a long break\n75 min focused today"
+        );
+
+        state.status = Status::Paused;
+        state.label = None;
+        state.sessions_today = 4;
+        assert_eq!(
+            tooltip(&state, 100, now),
+            "Paused\n24 min left\n4 sessions until a long break\n100 min focused today"
+        );
+
+        state.status = Status::Idle;
+        state.config.long_break_every = 0;
+        assert_eq!(tooltip(&state, 0, now), "Ready\n0 min focused today");
+    }
+}
@@ -0,0 +1,50 @@
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::Duration;
+use notify::{RecursiveMode, Watcher};
+
+/// How long to wait for a burst of events (write + rename, editors saving twice) to settle.
+const SETTLE: Duration = Duration::from_millis(150);
+
+/// Watch the data directory on a background thread and call `on_change` with the
+/// file name of each file that changed, once per burst of events.
+pub fn watch_dir(dir: PathBuf, on_change: impl Fn(&str) + Send + 'static) {
+    std::thread::spawn(move || {
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = match notify::recommended_watcher(tx) {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                eprintln!("Could not start file watcher: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = watcher.watch(&dir, RecursiveMode::NonRecursive) {
+            eprintln!("Could not watch {}: {}", dir.display(), e);
+            return;
+        }
+
+        while let Ok(first) = rx.recv() {
+            let mut changed = BTreeSet::new();
+            let mut collect = |event: notify::Result<notify::Event>| {
+                if let Ok(event) = event {
+                    if event.kind.is_access() {
+                        return;
+                    }
+                    for path in event.paths {
+                        if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                            changed.insert(name.to_string());
+                        }
+                    }
+                }
+            };
+            collect(first);
+            while let Ok(event) = rx.recv_timeout(SETTLE) {
+                collect(event);
+            }
+            for name in &changed {
+                on_change(name);
+            }
+        }
+    });
+}
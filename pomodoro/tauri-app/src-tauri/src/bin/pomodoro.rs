@@ -0,0 +1,391 @@
+//! `pomodoro`: drive the timer from a terminal or a script.
+//!
+//! Talks to the running app over its control socket. Without one, works on `~/.pomodoro`
+//! through the same controller as the app, so later calls see the session; the app itself
+//! always starts idle and does not resume it.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use chrono::Local;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use pomodoro_core::action::Action;
+use pomodoro_core::control::{Client, Query, Reply, Request};
+use pomodoro_core::controller::{Controller, Shell};
+use pomodoro_core::events::LifecycleEvent;
+use pomodoro_core::export::{self, ExportFilter, ExportFormat};
+use pomodoro_core::import::{self, CsvMapping, ImportFormat, ImportSummary};
+use pomodoro_core::persistence::{self, SessionRecord};
+use pomodoro_core::settings::EncryptionKey;
+use pomodoro_core::timer::TimerState;
+use pomodoro_core::{i18n, settings, tray};
+
+#[derive(Parser)]
+#[command(name = "pomodoro", version, about = "Control the Pomodoro timer")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Start a focus session
+    Start {
+        /// Length in minutes (default: the focus length from settings)
+        #[arg(long)]
+        minutes: Option<u64>,
+        #[arg(long)]
+        label: Option<String>,
+    },
+    /// Pause the running session
+    Pause,
+    /// Resume a paused session
+    Resume,
+    /// Give up the focus session (or skip the break)
+    Forfeit {
+        #[arg(long)]
+        reason: Option<String>,
+    },
+    /// Show the timer: `text`, `json`, or a title template such as "{icon} {time}"
+    Status {
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
+    /// Daily totals, oldest first
+    History {
+        #[arg(long, default_value_t = 7, value_parser = clap::value_parser!(i64).range(1..=36500))]
+        days: i64,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Write history to a file (default: ~/.pomodoro/exports/)
+    Export {
+        #[arg(long, value_enum, default_value_t = Format::Csv)]
+        format: Format,
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// First day, YYYY-MM-DD
+        #[arg(long)]
+        from: Option<String>,
+        /// Last day, YYYY-MM-DD
+        #[arg(long)]
+        to: Option<String>,
+        /// Only sessions with this tag (repeatable)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+    },
+    /// Merge another tool's export into history; days already in history are skipped
+    Import {
+        file: PathBuf,
+        #[arg(long, value_enum)]
+        format: Source,
+        #[command(flatten)]
+        mapping: Mapping,
+    },
+    /// Encrypt history in place (JSON backend) and keep it encrypted
+    Encrypt {
+        /// Use this file's contents as the key
+        #[arg(long)]
+        keyfile: Option<PathBuf>,
+        /// Otherwise the passphrase, read from this variable by whoever opens history (the app)
+        #[arg(long, default_value = "POMODORO_PASSPHRASE", conflicts_with = "keyfile")]
+        passphrase_env: String,
+    },
+    /// Decrypt history in place and turn encryption off
+    Decrypt,
+    /// Print lifecycle events from the running app as JSON lines, until it exits
+    Watch,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Source {
+    /// Any CSV, with the columns named below
+    Csv,
+    /// A Toggl Track detailed report
+    Toggl,
+    /// This tool's own JSON Lines export
+    Jsonl,
+}
+
+/// Header names of the columns in a `--format csv` file
+#[derive(Args)]
+struct Mapping {
+    /// Column with the start time (or use --date)
+    #[arg(long)]
+    start: Option<String>,
+    #[arg(long)]
+    end: Option<String>,
+    #[arg(long)]
+    date: Option<String>,
+    /// Column with the length in minutes (or use --end)
+    #[arg(long)]
+    duration: Option<String>,
+    #[arg(long)]
+    label: Option<String>,
+    #[arg(long)]
+    tags: Option<String>,
+    /// Column where "false", "0" or "no" marks a forfeited session
+    #[arg(long)]
+    completed: Option<String>,
+    /// chrono format of --start and --end (default "%Y-%m-%d %H:%M:%S")
+    #[arg(long)]
+    datetime_format: Option<String>,
+    /// chrono format of --date (default "%Y-%m-%d")
+    #[arg(long)]
+    date_format: Option<String>,
+}
+
+impl Mapping {
+    fn format(self, source: Source) -> ImportFormat {
+        match source {
+            Source::Csv => ImportFormat::Csv(Box::new(CsvMapping {
+                start: self.start,
+                end: self.end,
+                date: self.date,
+                duration_minutes: self.duration,
+                label: self.label,
+                tags: self.tags,
+                completed: self.completed,
+                datetime_format: self.datetime_format,
+                date_format: self.date_format,
+            })),
+            Source::Toggl => ImportFormat::Toggl,
+            Source::Jsonl => ImportFormat::Jsonl,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Csv,
+    Jsonl,
+    Ics,
+}
+
+impl From<Format> for ExportFormat {
+    fn from(format: Format) -> Self {
+        match format {
+            Format::Csv => ExportFormat::Csv,
+            Format::Jsonl => ExportFormat::Jsonl,
+            Format::Ics => ExportFormat::Ics,
+        }
+    }
+}
+
+// Disk only: no tray, windows or webviews to update
+struct DataDir;
+
+impl Shell for DataDir {
+    fn save_state(&self, state: &TimerState, _now: bool) {
+        persistence::save_state(state);
+    }
+    fn load_state(&self) -> TimerState {
+        persistence::load_state()
+    }
+    fn log_session(&self, record: SessionRecord) {
+        persistence::store().log_session(&record);
+    }
+    fn emit(&self, _event: &LifecycleEvent) {}
+    fn tick(&self, _state: &TimerState) {}
+    fn update_menu(&self, _state: &TimerState) {}
+    fn refresh_tray(&self, _state: &TimerState) {}
+    fn show_sanctuary(&self, _show: bool) {}
+    fn show_micro_break(&self) {}
+}
+
+// The stored state with durations from settings, brought up to now
+fn controller(settings: &settings::Settings) -> Controller {
+    let mut state = persistence::load_state();
+    state.config = settings.timer_config();
+    let controller = Controller::new(state, Box::new(DataDir));
+    controller.tick();
+    controller
+}
+
+// The running app, if it is listening
+fn app() -> Option<Client> {
+    Client::connect(&persistence::get_pomodoro_dir()).ok()
+}
+
+fn ask(client: &mut Client, request: Request) -> Result<Reply, String> {
+    match client.request(&request).map_err(|e| e.to_string())? {
+        Reply::Error(error) => Err(error.message),
+        reply => Ok(reply),
+    }
+}
+
+fn state_from(reply: Reply) -> Result<TimerState, String> {
+    match reply {
+        Reply::State(state) => Ok(state),
+        other => Err(format!("unexpected reply: {:?}", other)),
+    }
+}
+
+fn watch() -> Result<String, String> {
+    let mut client = app().ok_or("the app is not running")?;
+    ask(&mut client, Request::Subscribe { subscribe: true })?;
+    while let Some(event) = client.next_event().map_err(|e| e.to_string())? {
+        println!("{}", serde_json::to_string(&event).map_err(|e| e.to_string())?);
+    }
+    Ok(String::new())
+}
+
+fn import_file(file: PathBuf, format: ImportFormat) -> Result<String, String> {
+    let summary = match app() {
+        // The app merges it, so its stats and tray pick the days up
+        Some(mut client) => {
+            let import = file.canonicalize().map_err(|e| format!("Could not read {}: {}", file.display(), e))?;
+            match ask(&mut client, Request::Import { import, format })? {
+                Reply::Imported(summary) => summary,
+                other => return Err(format!("unexpected reply: {:?}", other)),
+            }
+        }
+        None => import::import_file(&file, &format)?,
+    };
+    let ImportSummary { days_added, days_skipped, rows_skipped } = summary;
+    Ok(format!(
+        "Imported {} days ({} already in history, {} unreadable rows skipped)",
+        days_added, days_skipped, rows_skipped
+    ))
+}
+
+fn set_encryption(key: Option<EncryptionKey>) -> Result<String, String> {
+    let encrypted = match app() {
+        // The app holds history open; it has to switch over itself
+        Some(mut client) => {
+            let request = match key {
+                Some(encrypt) => Request::Encrypt { encrypt },
+                None => Request::Decrypt { decrypt: true },
+            };
+            match ask(&mut client, request)? {
+                Reply::Encrypted(encrypted) => encrypted,
+                other => return Err(format!("unexpected reply: {:?}", other)),
+            }
+        }
+        None => match key {
+            Some(key) => persistence::encrypt_history(key).map(|()| true)?,
+            None => persistence::decrypt_history().map(|()| false)?,
+        },
+    };
+    Ok(if encrypted { "History is encrypted" } else { "History is decrypted" }.to_string())
+}
+
+fn focus_today() -> u32 {
+    let today = Local::now().format("%Y-%m-%d").to_string();
+    persistence::store()
+        .history_between(&today, &today)
+        .first()
+        .map_or(0, |entry| entry.total_focus_minutes)
+}
+
+fn status(state: &TimerState, format: &str, settings: &settings::Settings) -> Result<String, String> {
+    match format {
+        "text" => Ok(tray::tooltip(state, focus_today(), Local::now())),
+        "json" => serde_json::to_string_pretty(state).map_err(|e| e.to_string()),
+        template => {
+            let unknown = tray::unknown_placeholders(template);
+            if !unknown.is_empty() {
+                return Err(format!("unknown placeholders: {}", unknown.join(", ")));
+            }
+            // Plain digits: terminals rarely have the monospace ones
+            let format = tray::TitleFormat {
+                template: template.to_string(),
+                digits: tray::TitleDigits::Ascii,
+                ..settings.title_format()
+            };
+            Ok(tray::title(&format, state))
+        }
+    }
+}
+
+fn run(command: Command) -> Result<String, String> {
+    let settings = settings::load_settings();
+    i18n::set_locale(settings.locale.as_deref());
+    let action = match command {
+        Command::Start { minutes, label } => {
+            let duration = match minutes {
+                Some(0) => return Err("--minutes must be at least 1".to_string()),
+                Some(minutes) => Some(minutes.checked_mul(60).ok_or("--minutes is too large")?),
+                None => None,
+            };
+            Action::StartFocus { duration, label }
+        }
+        Command::Pause => Action::Pause,
+        Command::Resume => Action::Resume,
+        Command::Forfeit { reason } => Action::Forfeit { reason },
+        Command::Status { format } => {
+            let state = match app() {
+                Some(mut client) => state_from(ask(&mut client, Request::Query(Query::State))?)?,
+                None => controller(&settings).state(),
+            };
+            return status(&state, &format, &settings);
+        }
+        Command::History { days, json } => {
+            let now = Local::now();
+            let from = (now - chrono::Duration::days(days - 1)).format("%Y-%m-%d").to_string();
+            let to = now.format("%Y-%m-%d").to_string();
+            let entries = match app() {
+                // Includes sessions the app has not flushed to disk yet
+                Some(mut client) => match ask(&mut client, Request::Query(Query::History { from, to }))? {
+                    Reply::History(entries) => entries,
+                    other => return Err(format!("unexpected reply: {:?}", other)),
+                },
+                None => persistence::store().history_between(&from, &to),
+            };
+            if json {
+                return serde_json::to_string_pretty(&entries).map_err(|e| e.to_string());
+            }
+            let lines: Vec<String> = entries
+                .iter()
+                .map(|e| format!("{}  {:>3} done  {:>3} forfeited  {:>4} min", e.date, e.completed, e.forfeited, e.total_focus_minutes))
+                .collect();
+            return Ok(lines.join("\n"));
+        }
+        Command::Export { format, output, from, to, tags } => {
+            // Exports read the files: first get the running app's latest sessions into them
+            if let Some(mut client) = app() {
+                ask(&mut client, Request::Flush { flush: true })?;
+            }
+            let format = ExportFormat::from(format);
+            let path = output.unwrap_or_else(|| export::default_path(format));
+            let filter = ExportFilter { from, to, tags };
+            let count = export::export_to(&path, format, &filter).map_err(|e| e.to_string())?;
+            return Ok(format!("Exported {} items to {}", count, path.display()));
+        }
+        Command::Import { file, format, mapping } => return import_file(file, mapping.format(format)),
+        Command::Encrypt { keyfile, passphrase_env } => {
+            let key = match keyfile {
+                // settings.json keeps it, and the app may run from another directory
+                Some(path) => EncryptionKey::Keyfile {
+                    path: path.canonicalize().map_err(|e| format!("Could not read {}: {}", path.display(), e))?,
+                },
+                None => EncryptionKey::Passphrase { env: passphrase_env },
+            };
+            return set_encryption(Some(key));
+        }
+        Command::Decrypt => return set_encryption(None),
+        Command::Watch => return watch(),
+    };
+    let state = match app() {
+        Some(mut client) => state_from(ask(&mut client, Request::Action(action))?)?,
+        None => controller(&settings).dispatch(action).map_err(|e| e.message)?,
+    };
+    status(&state, "text", &settings)
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = run(cli.command);
+    persistence::flush_state();
+    match result {
+        Ok(output) => {
+            if !output.is_empty() {
+                println!("{}", output);
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("pomodoro: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -1,22 +1,30 @@
-mod timer;
-mod persistence;
-
-use std::sync::{Arc, Mutex};
+use pomodoro_core::{autostart, export, i18n, import, persistence, settings, timer, tray, watcher};
+use std::sync::{MutexGuard, OnceLock};
 use tauri::{
-    menu::{Menu, MenuBuilder, MenuItem, SubmenuBuilder},
+    image::Image,
+    menu::{CheckMenuItemBuilder, Menu, MenuBuilder, MenuItem, SubmenuBuilder},
     tray::{TrayIconBuilder, TrayIconEvent, MouseButton, MouseButtonState},
     Manager, AppHandle, Emitter, Runtime, LogicalSize, Size, WebviewWindow
 };
+use pomodoro_core::action::{Action, ActionError};
+use pomodoro_core::control::{self, Backend, Subscribers};
+use pomodoro_core::controller::{Controller, Shell};
+use pomodoro_core::events::LifecycleEvent;
+use pomodoro_core::history::HistoryService;
+use pomodoro_core::scheduler::{self, Scheduler};
+use pomodoro_core::shared::Shared;
 use timer::{TimerState, Status, TimerType};
-use persistence::History;
+use persistence::{History, HistoryEntry, SessionRecord};
+use settings::{EncryptionKey, Settings, Shortcuts};
+use tray::{IconKey, IconStyle, TitleFormat};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+use export::{ExportFilter, ExportFormat};
+use import::{ImportFormat, ImportSummary};
 use chrono::Local;
 
 #[tauri::command]
 fn hide_window(app_handle: tauri::AppHandle) {
-    if let Some(window) = app_handle.get_webview_window("main") {
-        let _ = window.set_fullscreen(false);
-        let _ = window.hide();
-    }
+    hide_sanctuary(&app_handle);
 }
 
 #[tauri::command]
@@ -34,65 +42,462 @@ fn hide_microbreak_window(app_handle: tauri::AppHandle) {
 }
 
 #[tauri::command]
-fn get_history() -> History {
-    persistence::load_history()
+fn get_history(history: tauri::State<HistoryService>) -> History {
+    history.snapshot()
+}
+
+// Inclusive YYYY-MM-DD range; includes yearly archives (e.g. for a year view).
+#[tauri::command]
+fn get_history_range(from: String, to: String, history: tauri::State<HistoryService>) -> Vec<HistoryEntry> {
+    history.between(&from, &to)
+}
+
+// Record a finished, forfeited or skipped session and tell the stats window.
+fn log_session(app: &AppHandle, record: SessionRecord) {
+    app.state::<HistoryService>().log(record);
+    refresh_focus_today(app);
+    let _ = app.emit("history-updated", ());
+}
+
+// History on disk changed under us (import, retention): re-read it and tell the stats window.
+fn reload_history(app: &AppHandle) {
+    app.state::<HistoryService>().reload();
+    refresh_focus_today(app);
+    let _ = app.emit("history-updated", ());
+}
+
+// Writes the export and returns where it went (defaults to ~/.pomodoro/exports/).
+#[tauri::command]
+fn export_history(format: ExportFormat, filter: Option<ExportFilter>, path: Option<String>, history: tauri::State<HistoryService>) -> Result<String, String> {
+    history.flush();
+    let path = path.map(std::path::PathBuf::from).unwrap_or_else(|| export::default_path(format));
+    export::export_to(&path, format, &filter.unwrap_or_default()).map_err(|e| e.to_string())?;
+    Ok(path.display().to_string())
+}
+
+// Merges another tool's export into history; days we already have are skipped.
+fn import_into(app: &AppHandle, path: &std::path::Path, format: &ImportFormat) -> Result<ImportSummary, String> {
+    // Queued sessions first, so the merge sees every day we have
+    app.state::<HistoryService>().flush();
+    let summary = import::import_file(path, format)?;
+    reload_history(app);
+    update_tray_menu(app, &app.state::<Controller>().state());
+    Ok(summary)
+}
+
+#[tauri::command]
+fn import_history(path: String, format: ImportFormat, app: AppHandle) -> Result<ImportSummary, String> {
+    import_into(&app, std::path::Path::new(&path), &format)
+}
+
+// Encrypts history.json (and archives) in place, or decrypts it for `None`; later reads/writes stay transparent.
+fn set_encryption(app: &AppHandle, key: Option<EncryptionKey>) -> Result<(), String> {
+    // Sessions still queued go out under the old setting before the files are rewritten
+    app.state::<HistoryService>().flush();
+    match key {
+        Some(key) => persistence::encrypt_history(key),
+        None => persistence::decrypt_history(),
+    }
+}
+
+#[tauri::command]
+fn encrypt_history(key: EncryptionKey, app: AppHandle) -> Result<(), String> {
+    set_encryption(&app, Some(key))
+}
+
+#[tauri::command]
+fn decrypt_history(app: AppHandle) -> Result<(), String> {
+    set_encryption(&app, None)
+}
+
+// Tray "Export…": full history in one format, then reveal the folder.
+fn export_from_tray(app: &AppHandle, format: ExportFormat) {
+    app.state::<HistoryService>().flush();
+    let path = export::default_path(format);
+    match export::export_to(&path, format, &ExportFilter::default()) {
+        Ok(_) => {
+            if let Some(dir) = path.parent() {
+                let _ = open::that(dir);
+            }
+        }
+        Err(e) => eprintln!("Export failed: {}", e),
+    }
 }
 
 #[tauri::command]
 fn open_stats(app_handle: tauri::AppHandle) {
-    if let Some(window) = app_handle.get_webview_window("stats") {
-        let _ = window.show();
-        let _ = window.set_focus();
+    show_window(&app_handle, "stats");
+}
+
+#[tauri::command]
+fn get_settings() -> Settings {
+    settings::load_settings()
+}
+
+// Last problem with a hand-edited settings.json, shown as a tray warning until fixed
+static SETTINGS_ERROR: Shared<Option<String>> = Shared::new(None);
+
+fn settings_error() -> MutexGuard<'static, Option<String>> {
+    SETTINGS_ERROR.lock(Clone::clone)
+}
+
+// Registered global shortcuts and the ones that could not be registered
+static SHORTCUTS: Shared<Option<(Shortcuts, Vec<String>)>> = Shared::new(None);
+
+// A panic part way through registering: register everything again next time
+fn registered_shortcuts() -> MutexGuard<'static, Option<(Shortcuts, Vec<String>)>> {
+    SHORTCUTS.lock(|_| None)
+}
+
+// (Re)register global shortcuts. Unparseable accelerators and ones taken by
+// another app are kept as conflicts for the settings window.
+fn register_shortcuts(app: &AppHandle, shortcuts: &Shortcuts) {
+    let mut registered = registered_shortcuts();
+    if registered.as_ref().is_some_and(|(current, _)| current == shortcuts) {
+        return;
+    }
+    let manager = app.global_shortcut();
+    let _ = manager.unregister_all();
+    let mut conflicts = Vec::new();
+    for (action, accelerator) in shortcuts.bindings() {
+        let result = manager.on_shortcut(accelerator, move |app, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                handle_action(app, action);
+            }
+        });
+        if let Err(e) = result {
+            conflicts.push(format!("shortcuts.{} ({}): {}", action, accelerator, e));
+        }
     }
+    for conflict in &conflicts {
+        eprintln!("Could not register shortcut {}", conflict);
+    }
+    *registered = Some((shortcuts.clone(), conflicts));
 }
 
 #[tauri::command]
-fn get_state(state: tauri::State<Arc<Mutex<TimerState>>>) -> TimerState {
-    let mut timer = state.lock().unwrap();
-    timer.calculate_remaining(false);
-    timer.clone()
+fn get_shortcut_conflicts() -> Vec<String> {
+    registered_shortcuts().as_ref().map(|(_, conflicts)| conflicts.clone()).unwrap_or_default()
 }
 
+// Quick capture: free text is the session label, `#words` become tags
 #[tauri::command]
-fn toggle_timer(state: tauri::State<Arc<Mutex<TimerState>>>, app: AppHandle) -> TimerState {
-    let mut timer = state.lock().unwrap();
-    timer.toggle();
-    let result = timer.clone();
-    persistence::save_state(&result);
-    drop(timer);
-    
-    // State change -> Update everything (Menu + Title)
-    update_tray_menu(&app, &result);
-    update_tray_title(&app, &result);
+fn capture_note(text: String, controller: tauri::State<Controller>, app: AppHandle) -> TimerState {
+    let (tags, words): (Vec<&str>, Vec<&str>) = text.split_whitespace().partition(|w| w.starts_with('#'));
+    let result = controller.update(|timer| {
+        timer.label = Some(words.join(" ")).filter(|label| !label.is_empty());
+        timer.tags = tags.into_iter().map(export::normalize_tag).filter(|t| !t.is_empty()).collect();
+    });
+
+    if let Some(window) = app.get_webview_window("capture") {
+        let _ = window.hide();
+    }
     result
 }
 
+// Timer control for the webviews. Same controller as the tray; errors come back as `{ code, message }`.
+// Durations are in seconds; `None` uses the configured length.
+#[tauri::command]
+fn start_focus(duration: Option<u64>, label: Option<String>, controller: tauri::State<Controller>) -> Result<TimerState, ActionError> {
+    controller.dispatch(Action::StartFocus { duration, label })
+}
+
+#[tauri::command]
+fn start_break(controller: tauri::State<Controller>) -> Result<TimerState, ActionError> {
+    controller.dispatch(Action::StartBreak)
+}
+
+// Sanctuary "Skip": end the break for real, not just hide the window
+#[tauri::command]
+fn skip_break(controller: tauri::State<Controller>) -> Result<TimerState, ActionError> {
+    controller.dispatch(Action::SkipBreak)
+}
+
+#[tauri::command]
+fn pause(controller: tauri::State<Controller>) -> Result<TimerState, ActionError> {
+    controller.dispatch(Action::Pause)
+}
+
+#[tauri::command]
+fn resume(controller: tauri::State<Controller>) -> Result<TimerState, ActionError> {
+    controller.dispatch(Action::Resume)
+}
+
+#[tauri::command]
+fn forfeit(reason: Option<String>, controller: tauri::State<Controller>) -> Result<TimerState, ActionError> {
+    controller.dispatch(Action::Forfeit { reason })
+}
+
+#[tauri::command]
+fn extend(controller: tauri::State<Controller>) -> Result<TimerState, ActionError> {
+    controller.dispatch(Action::Extend)
+}
+
+#[tauri::command]
+fn start_calm(duration: Option<u64>, controller: tauri::State<Controller>) -> Result<TimerState, ActionError> {
+    controller.dispatch(Action::StartCalm { duration })
+}
+
+#[tauri::command]
+fn hide_capture_window(app_handle: tauri::AppHandle) {
+    if let Some(window) = app_handle.get_webview_window("capture") {
+        let _ = window.hide();
+    }
+}
+
+// Switch the catalog for the `locale` setting; tray and windows re-render on `locale-changed`.
+// Returns whether the language changed.
+fn apply_locale(app: &AppHandle, new_settings: &Settings) -> bool {
+    let changed = i18n::set_locale(new_settings.locale.as_deref());
+    if changed {
+        let _ = app.emit("locale-changed", i18n::catalog());
+    }
+    changed
+}
+
+#[tauri::command]
+fn get_catalog() -> i18n::Catalog {
+    i18n::catalog()
+}
+
+// Apply durations to the running timer, refresh the tray and broadcast `settings-changed`.
+// Storage backend changes take effect on next launch.
+fn apply_settings(app: &AppHandle, new_settings: &Settings) {
+    set_title_format(new_settings.title_format());
+    set_icon_style(new_settings.tray_icon);
+    let result = app.state::<Controller>().update(|state| {
+        state.config = new_settings.timer_config();
+        // An idle focus timer shows the default length, so refresh it
+        if state.status == Status::Idle && state.timer_type == TimerType::Focus {
+            state.reset();
+        }
+    });
+
+    register_shortcuts(app, &new_settings.shortcuts);
+    update_tray_menu(app, &result);
+    let _ = app.emit("settings-changed", new_settings);
+}
+
+// settings.json changed on disk. A broken file is reported, never reverted:
+// the last good settings stay in effect until it is fixed.
+fn reload_settings(app: &AppHandle) {
+    match settings::read_settings() {
+        Ok(new_settings) => {
+            let had_error = settings_error().take().is_some();
+            let was_autostart = autostart::enabled();
+            if let Err(e) = autostart::sync(&new_settings) {
+                eprintln!("Could not update launch at login: {}", e);
+            }
+            register_shortcuts(app, &new_settings.shortcuts);
+            let locale_changed = apply_locale(app, &new_settings);
+            let title_changed = tray_title().as_ref().map(|(format, _)| format) != Some(&new_settings.title_format())
+                || tray_icon().0 != new_settings.tray_icon;
+            // Our own saves (settings window, tray toggle) land here too
+            let unchanged = !had_error
+                && !locale_changed
+                && !title_changed
+                && was_autostart == new_settings.launch_at_login
+                && app.state::<Controller>().state().config == new_settings.timer_config();
+            if unchanged {
+                return;
+            }
+            apply_settings(app, &new_settings);
+        }
+        Err(e) => {
+            eprintln!("Ignoring settings.json: {}", e);
+            *settings_error() = Some(e);
+            update_tray_menu(app, &app.state::<Controller>().state());
+        }
+    }
+}
+
+// Validates and saves. Encryption is managed by encrypt_history/decrypt_history, so it is kept as-is here;
+// while settings.json does not parse we can't tell what it is, so nothing is saved until it is fixed.
+#[tauri::command]
+fn update_settings(mut new_settings: Settings, app: AppHandle) -> Result<Settings, String> {
+    let errors = new_settings.validate();
+    if !errors.is_empty() {
+        return Err(errors.join("; "));
+    }
+    new_settings.encryption = settings::read_settings()
+        .map_err(|e| format!("Fix settings.json first: {}", e))?
+        .encryption;
+    autostart::sync(&new_settings)?;
+    settings::save_settings(&new_settings)?;
+    *settings_error() = None;
+    apply_locale(&app, &new_settings);
+
+    apply_settings(&app, &new_settings);
+    Ok(new_settings)
+}
+
+#[tauri::command]
+fn hide_settings_window(app_handle: tauri::AppHandle) {
+    if let Some(window) = app_handle.get_webview_window("settings") {
+        let _ = window.hide();
+    }
+}
+
+#[tauri::command]
+fn get_state(controller: tauri::State<Controller>) -> TimerState {
+    controller.state()
+}
+
+#[tauri::command]
+fn toggle_timer(controller: tauri::State<Controller>) -> Result<TimerState, ActionError> {
+    controller.dispatch(Action::Toggle)
+}
+
+// Tray title format, and the title last shown so ticks only touch the tray when it changes
+static TRAY_TITLE: Shared<Option<(TitleFormat, String)>> = Shared::new(None);
+
+// After a panic, keep the format and redraw the title
+fn tray_title() -> MutexGuard<'static, Option<(TitleFormat, String)>> {
+    TRAY_TITLE.lock(|title| title.as_ref().map(|(format, _)| (format.clone(), String::new())))
+}
+
+// Returns whether the format changed; the next update_tray_title redraws.
+fn set_title_format(format: TitleFormat) -> bool {
+    let mut title = tray_title();
+    if title.as_ref().is_some_and(|(current, _)| *current == format) {
+        return false;
+    }
+    *title = Some((format, String::new()));
+    true
+}
+
 // Only updates the text/icon, DOES NOT rebuild the menu.
 // Safe to call every second without closing the dropdown.
 fn update_tray_title(app: &AppHandle, state: &TimerState) {
+    let mut title = tray_title();
+    let (format, shown) = title.get_or_insert_with(|| (TitleFormat::default(), String::new()));
+    let rendered = tray::title(format, state);
+    if rendered == *shown {
+        return;
+    }
     if let Some(tray) = app.tray_by_id("main") {
-        let title = format!("{} {}", state.get_icon(), state.format_time());
-        let _ = tray.set_title(Some(&title));
+        let _ = tray.set_title(Some(&rendered));
+        *shown = rendered;
+    }
+}
+
+// Icon style, what was last drawn (`None` while the default icon is shown), and whether the
+// tray rejected an image. A rejected image is not retried until the style changes.
+static TRAY_ICON: Shared<(IconStyle, Option<(IconStyle, IconKey)>, bool)> = Shared::new((IconStyle::Ring, None, false));
+
+// After a panic, keep the style and redraw the icon
+fn tray_icon() -> MutexGuard<'static, (IconStyle, Option<(IconStyle, IconKey)>, bool)> {
+    TRAY_ICON.lock(|icon| (icon.0, None, false))
+}
+
+fn set_icon_style(style: IconStyle) {
+    let mut icon = tray_icon();
+    if icon.0 != style {
+        icon.0 = style;
+        icon.2 = false;
+    }
+}
+
+// Redraws only when the style, status or progress bucket changes, so most ticks do nothing.
+// Glyph style, or a tray that rejects the image, leaves the `{icon}` glyph in the title to show state.
+fn update_tray_icon(app: &AppHandle, state: &TimerState) {
+    let mut icon = tray_icon();
+    let Some(tray) = app.tray_by_id("main") else { return };
+    let drawn = (icon.0, tray::icon_key(state));
+    if icon.2 || icon.1.as_ref() == Some(&drawn) {
+        return;
+    }
+    match tray::render_icon(drawn.0, &drawn.1, tray::ICON_SIZE) {
+        Some(rgba) => {
+            let image = Image::new_owned(rgba, tray::ICON_SIZE, tray::ICON_SIZE);
+            match tray.set_icon(Some(image)) {
+                Ok(()) => icon.1 = Some(drawn),
+                Err(e) => {
+                    eprintln!("Tray rejected the progress icon; keeping the default: {}", e);
+                    icon.1 = None;
+                    icon.2 = true;
+                }
+            }
+        }
+        None => {
+            if icon.1.take().is_some() {
+                let _ = tray.set_icon(app.default_window_icon().cloned());
+            }
+        }
     }
 }
 
+// What the tray and the Sanctuary show for a state, for the scheduler to wake only when it
+// changes: the title, the icon's progress bucket, the tooltip's minutes, and the seconds
+// while the Sanctuary counts down on screen.
+fn shown_by(app: &AppHandle) -> impl Fn(&TimerState) -> (String, Option<IconKey>, u64, Option<u64>) {
+    let format = tray_title().as_ref().map(|(format, _)| format.clone()).unwrap_or_default();
+    let drawn = tray_icon().0 != IconStyle::Glyph;
+    let sanctuary = app.get_webview_window("main").is_some_and(|window| window.is_visible().unwrap_or(false));
+    move |state| {
+        (
+            tray::title(&format, state),
+            drawn.then(|| tray::icon_key(state)),
+            state.remaining.div_ceil(60),
+            sanctuary.then_some(state.remaining),
+        )
+    }
+}
+
+// Today's focus minutes from history, and the tooltip last shown.
+// Read from the in-memory history on transitions, never on a tick.
+static TRAY_TOOLTIP: Shared<(u32, String)> = Shared::new((0, String::new()));
+
+// After a panic, keep today's minutes and redraw the tooltip
+fn tray_tooltip() -> MutexGuard<'static, (u32, String)> {
+    TRAY_TOOLTIP.lock(|tooltip| (tooltip.0, String::new()))
+}
+
+fn refresh_focus_today(app: &AppHandle) {
+    let minutes = app
+        .state::<HistoryService>()
+        .today()
+        .map_or(0, |entry| entry.total_focus_minutes);
+    tray_tooltip().0 = minutes;
+}
+
+fn update_tray_tooltip(app: &AppHandle, state: &TimerState) {
+    let mut tooltip = tray_tooltip();
+    let rendered = tray::tooltip(state, tooltip.0, Local::now());
+    if rendered == tooltip.1 {
+        return;
+    }
+    if let Some(tray) = app.tray_by_id("main") {
+        let _ = tray.set_tooltip(Some(&rendered));
+        tooltip.1 = rendered;
+    }
+}
+
+// Title, icon and tooltip; each only touches the tray when it changed, so this is cheap enough for every tick.
+fn refresh_tray(app: &AppHandle, state: &TimerState) {
+    update_tray_title(app, state);
+    update_tray_icon(app, state);
+    update_tray_tooltip(app, state);
+}
+
 // Rebuilds the proper menu based on state.
 // Only call this on valid state transitions.
 fn update_tray_menu(app: &AppHandle, state: &TimerState) {
+    refresh_focus_today(app);
     if let Some(tray) = app.tray_by_id("main") {
         let menu = build_menu(app, state);
         let _ = tray.set_menu(Some(menu));
     }
 }
 
-fn generate_history_chart(history: &History) -> String {
+fn generate_history_chart(history: &[HistoryEntry]) -> String {
     let mut chart = String::new();
     let days: Vec<String> = (0..7).rev().map(|i| {
         (Local::now() - chrono::Duration::days(i)).format("%Y-%m-%d").to_string()
     }).collect();
     
     // Calculate max for scale
-    let max_minutes = history.sessions.iter()
+    let max_minutes = history.iter()
         .filter(|s| days.contains(&s.date))
         .map(|s| s.total_focus_minutes)
         .max()
@@ -102,7 +507,7 @@ fn generate_history_chart(history: &History) -> String {
     let blocks = [" ", " ", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
 
     for day in days {
-        let minutes = history.sessions.iter()
+        let minutes = history.iter()
             .find(|s| s.date == day)
             .map(|s| s.total_focus_minutes)
             .unwrap_or(0);
@@ -117,50 +522,95 @@ fn generate_history_chart(history: &History) -> String {
 }
 
 fn build_menu(app: &AppHandle, state: &TimerState) -> Menu<tauri::Wry> {
-    let history = persistence::load_history();
+    let history = app.state::<HistoryService>().recent(7);
     let chart = generate_history_chart(&history);
     
     // Get today's stats
     let today = Local::now().format("%Y-%m-%d").to_string();
-    let sessions_today = history.sessions.iter()
+    let sessions_today = history.iter()
         .find(|s| s.date == today)
         .map(|s| s.completed)
         .unwrap_or(0);
 
-    let builder = MenuBuilder::new(app);
+    let mut builder = MenuBuilder::new(app);
+
+    // 0. Hand-edited settings.json that we could not apply
+    if let Some(error) = settings_error().as_ref() {
+        builder = builder
+            .text("settings_error", i18n::tr_args("menu-settings-error", &[("error", error.as_str().into())]))
+            .separator();
+    }
+    // History we could not open (e.g. encrypted and the passphrase is not set); fixed by a restart
+    if let Some(error) = persistence::store_error() {
+        builder = builder
+            .text("history_error", i18n::tr_args("menu-history-error", &[("error", error.into())]))
+            .separator();
+    }
     
     // 1. Alternating Control
     // If Idle or Paused -> Show Start options
     // If Focus/Break (Running) -> Show Pause
     
     let builder = match state.status {
+        Status::Focus if state.timer_type == TimerType::Focus && state.extensions < timer::MAX_EXTENSIONS => {
+             builder.text("toggle", i18n::tr("menu-pause"))
+                .text("extend", i18n::tr("menu-extend"))
+        },
         Status::Focus | Status::Break => {
-             builder.text("toggle", "Pause")
+             builder.text("toggle", i18n::tr("menu-pause"))
         },
         Status::Paused => {
-             builder.text("toggle", "Resume")
+             builder.text("toggle", i18n::tr("menu-resume"))
         },
         Status::Idle => {
-             // Start Focus Submenu
-             let start_menu = tauri::menu::SubmenuBuilder::new(app, "Start Focus")
-                .text("focus_25", "25 Minutes (Default)")
-                .text("focus_5", "5 Minutes")
-                .text("focus_15", "15 Minutes")
-                .text("focus_45", "45 Minutes")
-                .build().unwrap();
+             // Start Focus Submenu: configured default first, then the presets
+             let default_minutes = state.config.focus / 60;
+             let mut start_menu = tauri::menu::SubmenuBuilder::new(app, i18n::tr("menu-start-focus"))
+                .text("focus_default", i18n::tr_args("menu-minutes-default", &[("minutes", default_minutes.into())]));
+             for minutes in [5, 15, 25, 45] {
+                 if minutes != default_minutes {
+                     start_menu = start_menu.text(format!("focus_{}", minutes), i18n::tr_args("menu-minutes", &[("minutes", minutes.into())]));
+                 }
+             }
+             let start_menu = start_menu.build().unwrap();
              
              builder.item(&start_menu)
         }
     };
 
-    let builder = builder
-        .text("forfeit", "Forfeit");
+    // 2. Break controls: skip a running or waiting break, or take one now
+    let builder = if state.has_break() {
+        match state.status {
+            Status::Idle => builder.text("start_break", i18n::tr("menu-start-break")),
+            _ => builder,
+        }
+        .text("skip_break", i18n::tr("menu-skip-break"))
+    } else {
+        builder
+            .text("start_break", i18n::tr("menu-start-break-now"))
+            .text("forfeit", i18n::tr("menu-forfeit"))
+    };
 
     // 3. Calm Mode Submenu
-    let calm_menu = tauri::menu::SubmenuBuilder::new(app, "Calm Mode")
-        .text("calm_5", "5 Minutes (Default)")
-        .text("calm_15", "15 Minutes")
-        .text("calm_45", "45 Minutes")
+    let calm_default = state.config.calm / 60;
+    let mut calm_menu = tauri::menu::SubmenuBuilder::new(app, i18n::tr("menu-calm-mode"))
+        .text("calm_default", i18n::tr_args("menu-minutes-default", &[("minutes", calm_default.into())]));
+    for minutes in [5, 15, 45] {
+        if minutes != calm_default {
+            calm_menu = calm_menu.text(format!("calm_{}", minutes), i18n::tr_args("menu-minutes", &[("minutes", minutes.into())]));
+        }
+    }
+    let calm_menu = calm_menu.build().unwrap();
+
+    let launch_at_login = CheckMenuItemBuilder::with_id("autostart", i18n::tr("menu-launch-at-login"))
+        .checked(autostart::enabled())
+        .build(app)
+        .unwrap();
+
+    let export_menu = tauri::menu::SubmenuBuilder::new(app, i18n::tr("menu-export"))
+        .text("export_csv", i18n::tr("menu-export-csv"))
+        .text("export_jsonl", i18n::tr("menu-export-jsonl"))
+        .text("export_ics", i18n::tr("menu-export-ics"))
         .build().unwrap();
         
     builder
@@ -168,83 +618,293 @@ fn build_menu(app: &AppHandle, state: &TimerState) -> Menu<tauri::Wry> {
         
         // 4. Session Count (Disabled)
         .separator()
-        .item(&MenuItem::with_id(app, "sessions", &format!("{} Full Sessions Today", sessions_today), false, None::<&str>).unwrap())
+        .item(&MenuItem::with_id(app, "sessions", i18n::tr_args("menu-sessions-today", &[("count", sessions_today.into())]), false, None::<&str>).unwrap())
         
         // 5. Weekly History Chart (Disabled/Info)
-        .item(&MenuItem::with_id(app, "history", &format!("History: {}", chart), false, None::<&str>).unwrap())
+        .item(&MenuItem::with_id(app, "history", i18n::tr_args("menu-history", &[("chart", chart.into())]), false, None::<&str>).unwrap())
         
-        // 6. How to Use
+        .item(&export_menu)
+
+        // 6. Settings & How to Use
         .separator()
-        .text("about", "How to Use")
+        .text("settings", i18n::tr("menu-settings"))
+        .item(&launch_at_login)
+        .text("about", i18n::tr("menu-how-to-use"))
         
         .separator()
-        .text("quit", "Quit")
+        .text("quit", i18n::tr("menu-quit"))
         .build()
         .unwrap()
 }
 
+fn show_window(app: &AppHandle, label: &str) -> Option<WebviewWindow> {
+    let window = app.get_webview_window(label)?;
+    let _ = window.show();
+    let _ = window.set_focus();
+    Some(window)
+}
+
+fn show_sanctuary(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.set_fullscreen(true);
+    }
+    show_window(app, "main");
+}
+
+fn hide_sanctuary(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.set_fullscreen(false);
+        let _ = window.hide();
+    }
+}
+
+// The tick thread, stopped on exit
+static SCHEDULER: Shared<Option<Scheduler>> = Shared::new(None);
+
+// Only ever set or taken; a panic in between loses the handle, and exit skips the shutdown
+fn scheduler_handle() -> MutexGuard<'static, Option<Scheduler>> {
+    SCHEDULER.lock(|_| None)
+}
+
+// Set in setup; until then the controller's side effects other than saving do nothing.
+static APP: OnceLock<AppHandle> = OnceLock::new();
+
+// Control socket connections watching lifecycle events
+static SUBSCRIBERS: Subscribers = Subscribers::new();
+
+// The control socket's view of the app: the same controller and history as the tray.
+struct AppBackend(AppHandle);
+
+impl Backend for AppBackend {
+    fn dispatch(&self, action: Action) -> Result<TimerState, ActionError> {
+        self.0.state::<Controller>().dispatch(action)
+    }
+
+    fn state(&self) -> TimerState {
+        self.0.state::<Controller>().state()
+    }
+
+    fn history(&self, from: &str, to: &str) -> Vec<HistoryEntry> {
+        self.0.state::<HistoryService>().between(from, to)
+    }
+
+    fn flush(&self) {
+        self.0.state::<HistoryService>().flush();
+    }
+
+    fn import(&self, path: &std::path::Path, format: &ImportFormat) -> Result<ImportSummary, String> {
+        import_into(&self.0, path, format)
+    }
+
+    fn set_encryption(&self, key: Option<EncryptionKey>) -> Result<(), String> {
+        set_encryption(&self.0, key)
+    }
+}
+
+// The controller's side effects on top of Tauri.
+struct TauriShell;
+
+impl Shell for TauriShell {
+    // A failed write panics in the store; log it here rather than unwind into a tray callback
+    fn save_state(&self, state: &TimerState, now: bool) {
+        let saved = std::panic::catch_unwind(|| {
+            if now {
+                persistence::save_state(state);
+            } else {
+                persistence::save_state_later(state);
+            }
+        });
+        if saved.is_err() {
+            eprintln!("Could not save timer state; will retry on the next save");
+        }
+    }
+
+    fn load_state(&self) -> TimerState {
+        persistence::load_state()
+    }
+
+    fn log_session(&self, record: SessionRecord) {
+        if let Some(app) = APP.get() {
+            log_session(app, record);
+        }
+    }
+
+    fn emit(&self, event: &LifecycleEvent) {
+        if let Some(app) = APP.get() {
+            let _ = app.emit(event.name(), event);
+        }
+        SUBSCRIBERS.publish(event);
+    }
+
+    fn tick(&self, state: &TimerState) {
+        if let Some(app) = APP.get() {
+            let _ = app.emit("timer-tick", state);
+        }
+    }
+
+    fn update_menu(&self, state: &TimerState) {
+        if let Some(app) = APP.get() {
+            update_tray_menu(app, state);
+        }
+    }
+
+    fn refresh_tray(&self, state: &TimerState) {
+        if let Some(app) = APP.get() {
+            refresh_tray(app, state);
+        }
+    }
+
+    fn show_sanctuary(&self, show: bool) {
+        let Some(app) = APP.get() else { return };
+        if show {
+            show_sanctuary(app);
+        } else {
+            hide_sanctuary(app);
+        }
+        // Its countdown needs a tick every second, so re-plan now that it is (not) on screen
+        app.state::<Controller>().waker().wake();
+    }
+
+    fn show_micro_break(&self) {
+        if let Some(window) = APP.get().and_then(|app| show_window(app, "microbreak")) {
+            let _ = window.set_always_on_top(true);
+        }
+    }
+}
+
+// Tray menu items and global shortcuts
+fn handle_action(app_handle: &AppHandle, id: &str) {
+    let controller = app_handle.state::<Controller>();
+    if let Some(action) = Action::from_menu_id(id) {
+        if let Err(e) = controller.dispatch(action) {
+            eprintln!("Ignoring {}: {}", id, e.message);
+        }
+        return;
+    }
+    match id {
+        // Windows
+        "stats" => {
+            show_window(app_handle, "stats");
+        },
+        "capture" => {
+            if show_window(app_handle, "capture").is_some() {
+                let _ = app_handle.emit_to("capture", "capture-open", &controller.state());
+            }
+        },
+        
+        // Export
+        "export_csv" => export_from_tray(app_handle, ExportFormat::Csv),
+        "export_jsonl" => export_from_tray(app_handle, ExportFormat::Jsonl),
+        "export_ics" => export_from_tray(app_handle, ExportFormat::Ics),
+
+        "settings_error" | "history_error" => {
+            let _ = open::that(settings::settings_path());
+        },
+        "autostart" => {
+            // Saving over an invalid settings.json would replace it with defaults
+            let mut current = match settings::read_settings() {
+                Ok(current) => current,
+                Err(e) => {
+                    eprintln!("Not toggling launch at login: {}", e);
+                    *settings_error() = Some(e);
+                    update_tray_menu(app_handle, &controller.state());
+                    return;
+                }
+            };
+            current.launch_at_login = !autostart::enabled();
+            match autostart::sync(&current) {
+                Ok(()) => {
+                    let _ = settings::save_settings(&current);
+                    let _ = app_handle.emit("settings-changed", &current);
+                }
+                Err(e) => eprintln!("Could not update launch at login: {}", e),
+            }
+        },
+        "settings" => {
+            show_window(app_handle, "settings");
+        },
+
+        // Links
+        "about" => {
+            let _ = open::that("https://vrushank.in/pomodoro#how-to-use");
+        },
+        "quit" => {
+            app_handle.exit(0);
+        }
+        _ => {}
+    }
+    let result = controller.state();
+    
+    // Menu Interaction: Update menu structure on command
+    update_tray_menu(app_handle, &result);
+    refresh_tray(app_handle, &result);
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let timer_state = Arc::new(Mutex::new(persistence::load_state()));
-    let timer_for_setup = timer_state.clone();
-    let timer_for_event = timer_state.clone();
-    let timer_for_tick = timer_state.clone();
+    let mut initial_state = persistence::load_state();
+    let startup_settings = match settings::read_settings() {
+        Ok(settings) => {
+            // Pick up a moved executable, or a setting changed while we were not running
+            if let Err(e) = autostart::sync(&settings) {
+                eprintln!("Could not update launch at login: {}", e);
+            }
+            settings
+        }
+        Err(e) => {
+            *settings_error() = Some(e);
+            Settings::default()
+        }
+    };
+    initial_state.config = startup_settings.timer_config();
+    i18n::set_locale(startup_settings.locale.as_deref());
+    set_title_format(startup_settings.title_format());
+    set_icon_style(startup_settings.tray_icon);
+    let controller = Controller::new(initial_state, Box::new(TauriShell));
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .manage(timer_state)
-        .invoke_handler(tauri::generate_handler![get_state, toggle_timer, hide_window, hide_stats_window, hide_microbreak_window, get_history, open_stats])
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .manage(controller)
+        .invoke_handler(tauri::generate_handler![get_state, toggle_timer, hide_window, hide_stats_window, hide_microbreak_window, get_history, get_history_range, export_history, import_history, encrypt_history, decrypt_history, open_stats, get_settings, update_settings, hide_settings_window, get_shortcut_conflicts, capture_note, start_focus, start_break, skip_break, pause, resume, forfeit, extend, start_calm, hide_capture_window, get_catalog])
         .setup(move |app| {
             let app_handle = app.handle().clone();
-            let mut state = timer_for_setup.lock().unwrap();
-            
+            let _ = APP.set(app_handle.clone());
+            persistence::apply_retention();
+            app.manage(HistoryService::new(persistence::store()));
+
             // Force Passive Startup: Always start fresh/idle
-            state.reset();
-            persistence::save_state(&state);
+            let state = app.state::<Controller>().update(TimerState::reset);
             
             // Explicitly hide main window and unset fullscreen (fixes macOS resume ghosting)
-            if let Some(window) = app_handle.get_webview_window("main") {
-                let _ = window.set_fullscreen(false);
-                let _ = window.hide();
-            }
+            hide_sanctuary(&app_handle);
             
             // Ensure micro-break window is hidden on startup
             if let Some(window) = app_handle.get_webview_window("microbreak") {
                 let _ = window.hide();
             }
+
+            register_shortcuts(&app_handle, &startup_settings.shortcuts);
             
-            // Build initial menu and title
+            // Build initial menu; the title, icon and tooltip are set once the tray exists
             let menu = build_menu(&app_handle, &state);
-            let title = format!("{} {}", state.get_icon(), state.format_time());
+            refresh_focus_today(&app_handle);
             
             // Create system tray with ID first
             let _ = TrayIconBuilder::with_id("main")
                 .menu(&menu)
-                .title(&title)
                 .show_menu_on_left_click(false)
                 .on_tray_icon_event({
-                    let timer = timer_for_event.clone();
                     let app_handle = app_handle.clone();
-                    move |tray, event| {
+                    move |_tray, event| {
                          match event {
                             TrayIconEvent::Click {
                                 button: MouseButton::Left,
                                 button_state: MouseButtonState::Up,
                                 ..
                             } => {
-                                // Left Click: Toggle Timer
-                                let mut state = timer.lock().unwrap();
-                                state.toggle();
-                                
-                                let _ = tray.set_title(Some(&format!("{} {}", state.get_icon(), state.format_time())));
-
-                                let result = state.clone();
-                                persistence::save_state(&result);
-                                drop(state);
-                                
-                                // Update Menu structure (Pause <-> Resume)
-                                update_tray_menu(&app_handle, &result);
-                                update_tray_title(&app_handle, &result);
+                                // Left Click: Toggle Timer (updates the menu: Pause <-> Resume)
+                                let _ = app_handle.state::<Controller>().dispatch(Action::Toggle);
                             }
                             _ => {}
                         }
@@ -252,118 +912,78 @@ pub fn run() {
                 })
                 .on_menu_event({
                     let app_handle = app_handle.clone();
-                    let timer = timer_for_setup.clone();
                     move |_tray, event| {
-                        let mut state = timer.lock().unwrap();
-                        match event.id.as_ref() {
-                            // Start Focus Options
-                            "focus_25" => state.start_focus(1500),
-                            "focus_5" => state.start_focus(300),
-                            "focus_15" => state.start_focus(900),
-                            "focus_45" => state.start_focus(2700),
-                            
-                            // Calm Mode Options
-                            "calm_5" => {
-                                state.start_break(300);
-                                state.timer_type = TimerType::Calm;
-                                if let Some(window) = app_handle.get_webview_window("main") {
-                                    let _ = window.set_fullscreen(true);
-                                    let _ = window.show();
-                                    let _ = window.set_focus();
-                                }
-                            },
-                             "calm_15" => {
-                                state.start_break(900);
-                                state.timer_type = TimerType::Calm;
-                                if let Some(window) = app_handle.get_webview_window("main") {
-                                    let _ = window.set_fullscreen(true);
-                                    let _ = window.show();
-                                    let _ = window.set_focus();
-                                }
-                            },
-                             "calm_45" => {
-                                state.start_break(2700);
-                                state.timer_type = TimerType::Calm;
-                                if let Some(window) = app_handle.get_webview_window("main") {
-                                    let _ = window.set_fullscreen(true);
-                                    let _ = window.show();
-                                    let _ = window.set_focus();
-                                }
-                            },
-                            
-                            // Controls
-                            "toggle" => state.toggle(),
-                            "forfeit" => {
-                                let minutes = ((state.total - state.remaining) / 60) as u32;
-                                state.forfeit();
-                                persistence::log_session(false, minutes);
-                            },
-                            "reset" => state.reset(),
-                            
-                            // Links
-                            "about" => {
-                                let _ = open::that("https://vrushank.in/pomodoro#how-to-use");
-                            },
-                            "quit" => {
-                                app_handle.exit(0);
-                            }
-                            _ => {}
-                        }
-                        let result = state.clone();
-                        persistence::save_state(&result);
-                        drop(state);
-                        
-                        // Menu Interaction: Update menu structure on command
-                        update_tray_menu(&app_handle, &result);
-                        update_tray_title(&app_handle, &result);
+                        handle_action(&app_handle, event.id.as_ref());
                     }
                 })
                 .build(app)?;
+            refresh_tray(&app_handle, &state);
                 
-            // Background tick thread
+            // Tick at each deadline (when what is shown changes while running, midnight
+            // otherwise); transitions wake it to re-plan
             let app_handle_for_tick = app.handle().clone();
-            std::thread::spawn(move || {
-                loop {
-                    std::thread::sleep(std::time::Duration::from_millis(1000));
-                    let mut state = timer_for_tick.lock().unwrap();
-                    let old_time = state.format_time();
-                    
-                    // Check for micro-break trigger (every 5 minutes during focus)
-                    if state.should_trigger_micro_break() {
-                        if let Some(window) = app_handle_for_tick.get_webview_window("microbreak") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                            let _ = window.set_always_on_top(true);
-                        }
-                    }
-                    
-                    if let Some((completed, minutes)) = state.calculate_remaining(false) {
-                        persistence::log_session(completed, minutes);
-                        
-                        // Session Finished -> Show Sanctuary (ONLY for Focus sessions)
-                        if completed {
-                            if let Some(window) = app_handle_for_tick.get_webview_window("main") {
-                                let _ = window.set_fullscreen(true);
-                                let _ = window.show();
-                                let _ = window.set_focus();
-                            }
-                        }
-                    }
-                    let state_clone = state.clone();
-                    let _ = app_handle_for_tick.emit("timer-tick", &state_clone);
-                    
-                    let new_time = state_clone.format_time();
-                    
-                    // ONLY update title on tick, never menu structure (prevents closing bug)
-                    if old_time != new_time {
-                        persistence::save_state(&state_clone);
-                        update_tray_title(&app_handle_for_tick, &state_clone);
-                    }
+            let mut retention_date = Local::now().date_naive();
+            let waker = app.state::<Controller>().waker();
+            let ticker = Scheduler::spawn(waker, move || {
+                // New day -> archive/compact history once
+                let today = Local::now().date_naive();
+                if today != retention_date {
+                    retention_date = today;
+                    persistence::apply_retention();
+                    reload_history(&app_handle_for_tick);
                 }
+
+                let state = app_handle_for_tick.state::<Controller>().tick();
+                let shown = shown_by(&app_handle_for_tick);
+                scheduler::next_deadline(&state, Local::now(), shown)
+            });
+            *scheduler_handle() = Some(ticker);
+
+            // External writers (SwiftBar script, team scripts, editors) -> adopt their
+            // state.json, apply hand-edited settings.json
+            let app_handle_for_watch = app.handle().clone();
+            watcher::watch_dir(persistence::get_pomodoro_dir(), move |name| {
+                if name == "settings.json" {
+                    reload_settings(&app_handle_for_watch);
+                    return;
+                }
+                if name != "state.json" {
+                    return;
+                }
+                // Whoever wrote it (e.g. the `pomodoro` CLI) may have logged sessions too
+                if app_handle_for_watch.state::<Controller>().adopt(persistence::reload_external_state) {
+                    reload_history(&app_handle_for_watch);
+                }
+            });
+
+            // The CLI and editor plugins; without it they fall back to state.json
+            let backend = std::sync::Arc::new(AppBackend(app_handle.clone()));
+            if let Err(e) = control::listen(&persistence::get_pomodoro_dir(), backend, &SUBSCRIBERS) {
+                eprintln!("Control socket unavailable: {}", e);
+            }
+
+            // OS shutdown / logout / Ctrl-C: persist before going down
+            let app_handle_for_signal = app.handle().clone();
+            let _ = ctrlc::set_handler(move || {
+                persistence::flush_state();
+                app_handle_for_signal.state::<HistoryService>().flush();
+                app_handle_for_signal.exit(0);
             });
 
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                // Stop ticking first so the last state written is the last one flushed
+                if let Some(ticker) = scheduler_handle().take() {
+                    ticker.shutdown(std::time::Duration::from_secs(1));
+                }
+                persistence::flush_state();
+                if let Some(history) = app.try_state::<HistoryService>() {
+                    history.flush();
+                }
+            }
+        });
 }
@@ -0,0 +1,34 @@
+import { initI18n } from './i18n.js';
+
+const { invoke } = window.__TAURI__.core;
+const { listen } = window.__TAURI__.event;
+
+const elements = {
+    form: document.getElementById('capture-form'),
+    text: document.getElementById('capture-text'),
+};
+
+// Show what was captured so far, so a second capture edits rather than replaces blindly
+function render(state) {
+    const tags = (state.tags || []).map(tag => `#${tag}`);
+    elements.text.value = [state.label || '', ...tags].filter(Boolean).join(' ');
+    elements.text.focus();
+    elements.text.select();
+}
+
+// Init
+document.addEventListener('DOMContentLoaded', () => {
+    initI18n();
+    listen('capture-open', (event) => render(event.payload));
+
+    elements.form.addEventListener('submit', (event) => {
+        event.preventDefault();
+        invoke('capture_note', { text: elements.text.value });
+    });
+
+    document.addEventListener('keydown', (event) => {
+        if (event.key === 'Escape') {
+            invoke('hide_capture_window').catch(() => window.close());
+        }
+    });
+});
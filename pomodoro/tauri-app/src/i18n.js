@@ -0,0 +1,47 @@
+// Shared translations: the catalog comes from the Rust side (Fluent files in core/locales)
+// so the tray menu and every window speak the same language.
+const { invoke } = window.__TAURI__.core;
+const { listen } = window.__TAURI__.event;
+
+let catalog = { locale: 'en-US', messages: {} };
+
+// Look up a message and fill in its `{$name}` variables
+export function t(id, args = {}) {
+  const pattern = catalog.messages[id];
+  if (pattern === undefined) return id;
+  return pattern.replace(/\{\s*\$(\w+)\s*\}/g, (match, name) => (name in args ? args[name] : match));
+}
+
+// Static text: <span data-i18n="message-id">English fallback</span>,
+// and placeholders: <input data-i18n-placeholder="message-id" placeholder="English fallback">
+function translateDocument() {
+  document.documentElement.lang = catalog.locale;
+  document.querySelectorAll('[data-i18n]').forEach((element) => {
+    element.textContent = t(element.dataset.i18n);
+  });
+  document.querySelectorAll('[data-i18n-placeholder]').forEach((element) => {
+    element.placeholder = t(element.dataset.i18nPlaceholder);
+  });
+}
+
+// Load the catalog, translate the page, and call `onChange` again whenever the language changes
+export async function initI18n(onChange = () => { }) {
+  try {
+    catalog = await invoke('get_catalog');
+  } catch (error) {
+    console.error('Failed to load translations:', error);
+  }
+  translateDocument();
+  onChange();
+
+  // A window without event permissions keeps the language it opened with
+  try {
+    await listen('locale-changed', (event) => {
+      catalog = event.payload;
+      translateDocument();
+      onChange();
+    });
+  } catch (error) {
+    console.error('Failed to listen for locale changes:', error);
+  }
+}
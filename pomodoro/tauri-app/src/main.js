@@ -1,4 +1,6 @@
 // Break Sanctuary - Tauri Integration
+import { t, initI18n } from './i18n.js';
+
 const { invoke } = window.__TAURI__.core;
 const { listen } = window.__TAURI__.event;
 
@@ -17,23 +19,8 @@ const elements = {
   bellSound: document.getElementById('bellSound'),
 };
 
-// Calming messages that rotate
-const messages = {
-  labels: [
-    'Take a breath',
-    'Be present',
-    'Rest your eyes',
-    'Let go',
-    'Be still',
-  ],
-  subtitles: [
-    'Your mind is settling',
-    'This moment is yours',
-    'Stillness restores',
-    'Peace flows through you',
-    'Breathe deeply',
-  ],
-};
+// Calming messages that rotate (catalog ids sanctuary-label-N / sanctuary-subtitle-N)
+const MESSAGE_COUNT = 5;
 
 let cursorTimeout = null;
 let lastState = null;
@@ -62,15 +49,6 @@ function updateDisplay(state) {
     rotateMessage();
   }
 
-  // Sanctuary window visibility logic
-  if (state.status !== 'break') {
-    // If we are showing but state is no longer break, close/hide
-    // In Tauri, the backend handles showing the window, but the frontend can handle closing it
-    if (state.status === 'idle' && lastState && lastState.status === 'break') {
-      completeBreak();
-    }
-  }
-
   // Determine break type styles
   if (state.total >= 600) {
     elements.sanctuary.classList.add('long-break');
@@ -83,15 +61,15 @@ function updateDisplay(state) {
 
 // Rotate calming messages
 function rotateMessage() {
-  const labelIndex = Math.floor(Math.random() * messages.labels.length);
-  const subtitleIndex = Math.floor(Math.random() * messages.subtitles.length);
+  const labelIndex = 1 + Math.floor(Math.random() * MESSAGE_COUNT);
+  const subtitleIndex = 1 + Math.floor(Math.random() * MESSAGE_COUNT);
 
   elements.timerLabel.style.opacity = '0';
   elements.timerSubtitle.style.opacity = '0';
 
   setTimeout(() => {
-    elements.timerLabel.textContent = messages.labels[labelIndex];
-    elements.timerSubtitle.textContent = messages.subtitles[subtitleIndex];
+    elements.timerLabel.textContent = t(`sanctuary-label-${labelIndex}`);
+    elements.timerSubtitle.textContent = t(`sanctuary-subtitle-${subtitleIndex}`);
     elements.timerLabel.style.opacity = '1';
     elements.timerSubtitle.style.opacity = '1';
   }, 300);
@@ -145,6 +123,7 @@ function hideCursor() {
 
 // Initialize
 async function init() {
+  await initI18n();
   createParticles();
 
   setTimeout(() => {
@@ -163,12 +142,23 @@ async function init() {
     updateDisplay(event.payload);
   });
 
+  // A break that ran to the end closes with the bell; a skipped one is hidden by the backend
+  await listen('break-ended', (event) => {
+    if (!event.payload.skipped) completeBreak();
+  });
+
   // Initial state fetch
   const initialState = await invoke('get_state');
   updateDisplay(initialState);
 
-  elements.skipBtn.addEventListener('click', () => {
-    invoke('hide_window');
+  // Skipping ends the break in the backend too, which hides this window
+  elements.skipBtn.addEventListener('click', async () => {
+    try {
+      await invoke('skip_break');
+    } catch (error) {
+      // No break to skip (it just ended): only close the Sanctuary
+      invoke('hide_window');
+    }
   });
 }
 
@@ -1,28 +1,11 @@
 // Micro-break - Eye care reminder
+import { t, initI18n } from './i18n.js';
+
 const { invoke } = window.__TAURI__.core;
 
-const messages = [
-  {
-    title: 'Look away from screen',
-    tip: 'Focus on something 20 feet away'
-  },
-  {
-    title: 'Rest your eyes',
-    tip: 'Blink slowly 10 times'
-  },
-  {
-    title: 'Relax your gaze',
-    tip: 'Close your eyes for a moment'
-  },
-  {
-    title: 'Stretch your neck',
-    tip: 'Roll your shoulders back gently'
-  },
-  {
-    title: 'Take a breath',
-    tip: 'Deep breath in, slow breath out'
-  }
-];
+// Catalog ids microbreak-title-N / microbreak-tip-N
+const MESSAGE_COUNT = 5;
+let messageIndex = 1;
 
 const DURATION = 10; // 10 seconds
 let remaining = DURATION;
@@ -38,15 +21,23 @@ const elements = {
 };
 
 function showRandomMessage() {
-  const msg = messages[Math.floor(Math.random() * messages.length)];
-  elements.message.textContent = msg.title;
-  elements.tip.textContent = msg.tip;
+  messageIndex = 1 + Math.floor(Math.random() * MESSAGE_COUNT);
+  renderMessage();
+}
+
+function renderMessage() {
+  elements.message.textContent = t(`microbreak-title-${messageIndex}`);
+  elements.tip.textContent = t(`microbreak-tip-${messageIndex}`);
+}
+
+function renderCountdown() {
+  elements.countdown.textContent = remaining;
+  elements.autoClose.textContent = t('microbreak-auto-close', { seconds: remaining });
 }
 
 function updateTimer() {
   remaining--;
-  elements.countdown.textContent = remaining;
-  elements.autoClose.textContent = remaining;
+  renderCountdown();
 
   // Update progress circle
   const circumference = 2 * Math.PI * 36;
@@ -67,7 +58,12 @@ function dismiss() {
   invoke('hide_microbreak_window');
 }
 
-function init() {
+async function init() {
+  await initI18n(() => {
+    renderMessage();
+    renderCountdown();
+  });
+
   // Show random message
   showRandomMessage();
 
@@ -91,8 +87,7 @@ document.addEventListener('DOMContentLoaded', init);
 // Reset on window show
 window.addEventListener('focus', () => {
   remaining = DURATION;
-  elements.countdown.textContent = remaining;
-  elements.autoClose.textContent = remaining;
+  renderCountdown();
   showRandomMessage();
   
   if (interval) {
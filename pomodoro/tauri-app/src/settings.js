@@ -0,0 +1,89 @@
+import { t, initI18n } from './i18n.js';
+
+const { invoke } = window.__TAURI__.core;
+const { listen } = window.__TAURI__.event;
+
+const elements = {
+    form: document.getElementById('settings-form'),
+    status: document.getElementById('settings-status'),
+    closeBtn: document.getElementById('close-btn'),
+    conflicts: document.getElementById('shortcut-conflicts'),
+};
+
+// The full settings object, so fields without a control here (storage, retention) round-trip unchanged
+let current = null;
+
+// Input names are paths into the settings object, e.g. `shortcuts.toggle`
+function getPath(object, path) {
+    return path.split('.').reduce((value, key) => value[key], object);
+}
+
+function setPath(object, path, value) {
+    const keys = path.split('.');
+    const last = keys.pop();
+    keys.reduce((value, key) => value[key], object)[last] = value;
+}
+
+function render(settings) {
+    current = settings;
+    for (const input of elements.form.querySelectorAll('input, select')) {
+        if (input.type === 'checkbox') {
+            input.checked = getPath(settings, input.name);
+        } else {
+            input.value = getPath(settings, input.name) ?? '';
+        }
+    }
+    elements.form.micro_break_interval_minutes.disabled = !settings.micro_breaks;
+    renderConflicts();
+}
+
+// Shortcuts the OS would not give us (taken by another app, or not a valid accelerator)
+async function renderConflicts() {
+    const conflicts = await invoke('get_shortcut_conflicts');
+    elements.conflicts.innerHTML = '';
+    for (const conflict of conflicts) {
+        const item = document.createElement('li');
+        item.textContent = conflict;
+        elements.conflicts.appendChild(item);
+    }
+}
+
+function readForm() {
+    const settings = structuredClone(current);
+    for (const input of elements.form.querySelectorAll('input, select')) {
+        const value = input.type === 'checkbox' ? input.checked
+            : input.type === 'number' ? Number(input.value)
+            : input.tagName === 'SELECT' ? input.value || null
+            : input.value;
+        setPath(settings, input.name, value);
+    }
+    return settings;
+}
+
+async function save(event) {
+    event.preventDefault();
+    try {
+        render(await invoke('update_settings', { newSettings: readForm() }));
+        elements.status.textContent = t('settings-saved');
+    } catch (error) {
+        elements.status.textContent = `${error}`;
+    }
+}
+
+// Init
+document.addEventListener('DOMContentLoaded', async () => {
+    initI18n();
+    render(await invoke('get_settings'));
+
+    // Saved elsewhere, or settings.json edited by hand
+    listen('settings-changed', (event) => render(event.payload));
+
+    elements.form.micro_breaks.addEventListener('change', (event) => {
+        elements.form.micro_break_interval_minutes.disabled = !event.target.checked;
+    });
+    elements.form.addEventListener('submit', save);
+    elements.closeBtn.addEventListener('click', () => {
+        elements.status.textContent = '';
+        invoke('hide_settings_window').catch(() => window.close());
+    });
+});
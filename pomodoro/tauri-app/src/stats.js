@@ -1,3 +1,5 @@
+import { t, initI18n } from './i18n.js';
+
 const { invoke } = window.__TAURI__.core;
 const { listen } = window.__TAURI__.event;
 
@@ -7,13 +9,13 @@ const elements = {
     weekChart: document.getElementById('week-chart'),
     weekLabels: document.getElementById('week-labels'),
     closeBtn: document.getElementById('close-btn'),
+    exportButtons: document.querySelectorAll('.export-btn'),
+    exportStatus: document.getElementById('export-status'),
 };
 
-const WEEK_DAYS = ['Sun', 'Mon', 'Tue', 'Wed', 'Thu', 'Fri', 'Sat'];
-
-function formatDate(dateStr) {
-    const date = new Date(dateStr);
-    return `${WEEK_DAYS[date.getDay()]}`;
+// Short weekday name, catalog ids stats-weekday-0 (Sunday) to stats-weekday-6
+function weekday(date) {
+    return t(`stats-weekday-${date.getDay()}`);
 }
 
 async function fetchAndRenderStats() {
@@ -71,7 +73,7 @@ function renderWeekChart(sessions, todayStr) {
     // Render bars
     dataPoints.forEach(point => {
         const dateObj = new Date(point.date);
-        const dayName = WEEK_DAYS[dateObj.getDay()];
+        const dayName = weekday(dateObj);
 
         // Bar Wrapper
         const wrapper = document.createElement('div');
@@ -80,7 +82,7 @@ function renderWeekChart(sessions, todayStr) {
         // Tooltip
         const tooltip = document.createElement('div');
         tooltip.className = 'bar-tooltip';
-        tooltip.textContent = `${point.minutes}m`;
+        tooltip.textContent = t('stats-bar-minutes', { minutes: point.minutes });
         wrapper.appendChild(tooltip);
 
         // Bar
@@ -101,9 +103,25 @@ function renderWeekChart(sessions, todayStr) {
     });
 }
 
+async function exportHistory(format) {
+    try {
+        const path = await invoke('export_history', { format });
+        elements.exportStatus.textContent = t('stats-saved-to', { path });
+    } catch (error) {
+        elements.exportStatus.textContent = t('stats-export-failed', { error });
+    }
+}
+
 // Init
 document.addEventListener('DOMContentLoaded', () => {
-    fetchAndRenderStats();
+    // Weekday labels come from the catalog, so redraw when the language changes
+    initI18n(fetchAndRenderStats);
+    // Sessions logged (or imported) while the window is open
+    listen('history-updated', fetchAndRenderStats);
+
+    elements.exportButtons.forEach(button => {
+        button.addEventListener('click', () => exportHistory(button.dataset.format));
+    });
 
     elements.closeBtn.addEventListener('click', () => {
         // We can just hide the window